@@ -0,0 +1,58 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::str::FromStr;
+use log::warn;
+use serde_derive::{Deserialize, Serialize};
+use sui_types::base_types::ObjectID;
+
+// names of account caps, stored as {"name": "0x<cap id>"}
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(transparent)]
+pub struct AccountNames {
+    pub accounts: BTreeMap<String, ObjectID>,
+}
+
+impl AccountNames {
+    // empty if file does not exist
+    pub fn load(path: &str) -> AccountNames {
+        let content = fs::read_to_string(path);
+        if content.is_err() {
+            return AccountNames::default();
+        }
+        return serde_json::from_str(&content.unwrap()).expect("could not parse accounts file");
+    }
+
+    pub fn save(&self, path: &str) {
+        let result = fs::write(path, serde_json::to_string_pretty(self).unwrap());
+        if result.is_err() {
+            warn!("could not save accounts to {}: {:?}", path, result);
+        }
+    }
+
+    pub fn insert(&mut self, name: String, cap_id: ObjectID) {
+        self.accounts.insert(name, cap_id);
+    }
+
+    pub fn name_of(&self, cap_id: &ObjectID) -> Option<&String> {
+        return self.accounts.iter().find(|(_, id)| *id == cap_id).map(|(name, _)| name);
+    }
+
+    // account is a name or cap id, the first owned cap if not provided,
+    // None if the cap is not owned
+    pub fn resolve(&self, account: Option<&str>, owned_caps: &Vec<ObjectID>) -> Option<ObjectID> {
+        if account.is_none() {
+            return owned_caps.first().cloned();
+        }
+        let account = account.unwrap();
+        let cap_id = if self.accounts.contains_key(account) {
+            Some(self.accounts[account])
+        } else {
+            ObjectID::from_str(account).ok()
+        };
+        if cap_id.is_none() || !owned_caps.contains(&cap_id.unwrap()) {
+            warn!("account {} is not an owned account cap", account);
+            return None;
+        }
+        return cap_id;
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use log::{info, warn};
+use serde_derive::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use crate::transaction::SubmitResult;
+
+// thresholds of automatic breakers, a trip cancels all orders and pauses quoting for cooldown_ms
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct BreakerConfig {
+    // realized loss in raw quote units over loss_window_ms, 0 disables
+    pub max_loss: f64,
+    pub loss_window_ms: u64,
+    // time without healthy oracle price
+    pub max_oracle_stale_ms: u64,
+    // share of failed rpc calls among the last rpc_window calls
+    pub max_rpc_error_rate: f64,
+    pub rpc_window: usize,
+    // relative distance of deepbook mid from oracle price
+    pub max_mid_divergence: f64,
+    pub max_consecutive_failures: usize,
+    pub cooldown_ms: u64,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        return BreakerConfig{
+            max_loss: 100_000_000.0,
+            loss_window_ms: 3_600_000,
+            max_oracle_stale_ms: 60_000,
+            max_rpc_error_rate: 0.5,
+            rpc_window: 20,
+            max_mid_divergence: 0.05,
+            max_consecutive_failures: 5,
+            cooldown_ms: 60_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Trip {
+    Loss { loss: f64, max: f64 },
+    OracleStale { age_ms: u64 },
+    RpcErrors { rate: f64 },
+    MidDivergence { deviation: f64 },
+    ConsecutiveFailures(usize),
+}
+
+pub struct CircuitBreaker {
+    pub config: BreakerConfig,
+    // (timestamp, cumulative pnl) in loss window
+    pnl: VecDeque<(u64, f64)>,
+    // outcomes of the last rpc calls, true is success
+    rpc: VecDeque<bool>,
+    failures: usize,
+    last_oracle_ms: u64,
+    tripped: Option<(Trip, u64)>,
+}
+
+impl CircuitBreaker {
+    // oracle staleness is counted from now_ms
+    pub fn new(config: BreakerConfig, now_ms: u64) -> CircuitBreaker {
+        return CircuitBreaker{ config, pnl: VecDeque::new(), rpc: VecDeque::new(), failures: 0, last_oracle_ms: now_ms, tripped: None }
+    }
+
+    // cumulative realized pnl in raw quote units, loss is the drop from the highest pnl in the window
+    pub fn record_pnl(&mut self, now_ms: u64, pnl: f64) {
+        self.pnl.push_back((now_ms, pnl));
+        while self.pnl.front().is_some() && now_ms.saturating_sub(self.pnl.front().unwrap().0) > self.config.loss_window_ms {
+            self.pnl.pop_front();
+        }
+    }
+
+    pub fn record_rpc(&mut self, ok: bool) {
+        self.rpc.push_back(ok);
+        while self.rpc.len() > self.config.rpc_window {
+            self.rpc.pop_front();
+        }
+    }
+
+    pub fn record_oracle(&mut self, now_ms: u64, healthy: bool) {
+        if healthy {
+            self.last_oracle_ms = now_ms;
+        }
+    }
+
+    // post only orders crossing the book are not failures, transport errors also count as rpc errors
+    pub fn record_submit(&mut self, result: &SubmitResult) {
+        match result {
+            SubmitResult::Success(_) => {
+                self.failures = 0;
+                self.record_rpc(true);
+            }
+            SubmitResult::MoveAbort { abort, .. } if abort.name.as_deref() == Some("EOrderCannotBeFullyPassive") => {
+                self.failures = 0;
+                self.record_rpc(true);
+            }
+            SubmitResult::MoveAbort { .. } | SubmitResult::Failed { .. } | SubmitResult::Rejected(_) => {
+                self.failures += 1;
+                self.record_rpc(true);
+            }
+            SubmitResult::Transport(_) | SubmitResult::Timeout => {
+                self.failures += 1;
+                self.record_rpc(false);
+            }
+            SubmitResult::NotSigned | SubmitResult::NoGas => self.failures += 1,
+        }
+    }
+
+    pub fn loss(&self) -> f64 {
+        if self.pnl.len() == 0 {
+            return 0.0;
+        }
+        let high = self.pnl.iter().map(|x| x.1).fold(f64::MIN, f64::max);
+        return (high - self.pnl.back().unwrap().1).max(0.0);
+    }
+
+    pub fn rpc_error_rate(&self) -> f64 {
+        // not enough calls to tell
+        if self.rpc.len() < self.config.rpc_window || self.rpc.len() == 0 {
+            return 0.0;
+        }
+        return self.rpc.iter().filter(|x| !**x).count() as f64 / self.rpc.len() as f64;
+    }
+
+    // first breaker tripped by current state, oracle_price and best bid/ask as used for quoting
+    pub fn check(&mut self, now_ms: u64, oracle_price: Option<f64>, best_bid: u64, best_ask: u64, price_decimals: u64) -> Option<Trip> {
+        let mut trip = None;
+        let loss = self.loss();
+        let age_ms = now_ms.saturating_sub(self.last_oracle_ms);
+        let rate = self.rpc_error_rate();
+        if self.config.max_loss > 0.0 && loss > self.config.max_loss {
+            trip = Some(Trip::Loss { loss, max: self.config.max_loss });
+        } else if age_ms > self.config.max_oracle_stale_ms {
+            trip = Some(Trip::OracleStale { age_ms });
+        } else if rate > self.config.max_rpc_error_rate {
+            trip = Some(Trip::RpcErrors { rate });
+        } else if self.failures >= self.config.max_consecutive_failures {
+            trip = Some(Trip::ConsecutiveFailures(self.failures));
+        } else if oracle_price.is_some() && best_bid > 0 && best_ask > 0 {
+            let mid = ((best_bid + best_ask) as f64 / 2.0) / price_decimals as f64;
+            let deviation = (mid - oracle_price.unwrap()).abs() / oracle_price.unwrap();
+            if deviation > self.config.max_mid_divergence {
+                trip = Some(Trip::MidDivergence { deviation });
+            }
+        }
+        if trip.is_some() {
+            warn!("circuit breaker tripped: {:?}, pausing for {} ms", trip, self.config.cooldown_ms);
+            self.tripped = Some((trip.clone().unwrap(), now_ms));
+        }
+        return trip;
+    }
+
+    // true during cooldown after a trip, counters start over once cooldown passes
+    pub fn paused(&mut self, now_ms: u64) -> bool {
+        if self.tripped.is_none() {
+            return false;
+        }
+        let (trip, at) = self.tripped.clone().unwrap();
+        if now_ms < at + self.config.cooldown_ms {
+            return true;
+        }
+        info!("circuit breaker reset after {:?}", trip);
+        self.tripped = None;
+        self.pnl.clear();
+        self.rpc.clear();
+        self.failures = 0;
+        self.last_oracle_ms = now_ms;
+        return false;
+    }
+}
+
+// manual halt, set by SIGINT/SIGTERM, existing flag file or POST /kill, the bot cancels all orders and exits
+#[derive(Clone)]
+pub struct KillSwitch {
+    killed: Arc<AtomicBool>,
+    file: Option<String>,
+}
+
+impl KillSwitch {
+    pub fn new(file: Option<String>) -> KillSwitch {
+        return KillSwitch{ killed: Arc::new(AtomicBool::new(false)), file }
+    }
+
+    pub fn kill(&self, reason: &str) {
+        if !self.killed.swap(true, Ordering::SeqCst) {
+            warn!("kill switch: {}", reason);
+        }
+    }
+
+    pub fn is_killed(&self) -> bool {
+        if self.file.is_some() && Path::new(self.file.as_ref().unwrap()).exists() {
+            self.kill(&format!("flag file {} exists", self.file.as_ref().unwrap()));
+        }
+        return self.killed.load(Ordering::SeqCst);
+    }
+
+    pub fn listen_signals(&self) {
+        let switch = self.clone();
+        tokio::spawn(
+            async move {
+                let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()).expect("could not listen to SIGTERM");
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => switch.kill("SIGINT"),
+                    _ = terminate.recv() => switch.kill("SIGTERM"),
+                }
+            }
+        );
+    }
+
+    // POST /kill halts the bot, GET /status returns running or killed
+    pub async fn listen_http(&self, port: u16) {
+        let listener = TcpListener::bind(("127.0.0.1", port)).await;
+        if listener.is_err() {
+            warn!("could not listen for kill requests on port {}: {:?}", port, listener.err());
+            return;
+        }
+        let listener = listener.unwrap();
+        info!("kill switch listening on 127.0.0.1:{}", port);
+        let switch = self.clone();
+        tokio::spawn(
+            async move {
+                loop {
+                    let accepted = listener.accept().await;
+                    if accepted.is_err() {
+                        continue;
+                    }
+                    let (mut stream, _) = accepted.unwrap();
+                    let mut buffer = [0u8; 1024];
+                    let read = stream.read(&mut buffer).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+                    let (status, body) = match http_route(&request) {
+                        Some("kill") => {
+                            switch.kill("http request");
+                            ("200 OK", "killed")
+                        }
+                        Some("status") => ("200 OK", if switch.is_killed() {"killed"} else {"running"}),
+                        _ => ("404 Not Found", "not found"),
+                    };
+                    let response = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, body.len(), body);
+                    let _ = stream.write_all(response.as_bytes()).await;
+                }
+            }
+        );
+    }
+}
+
+// kill or status from request line of http request
+pub fn http_route(request: &str) -> Option<&'static str> {
+    let line = request.lines().next()?;
+    let mut parts = line.split_whitespace();
+    return match (parts.next(), parts.next()) {
+        (Some("POST"), Some("/kill")) => Some("kill"),
+        (Some("GET"), Some("/status")) => Some("status"),
+        _ => None,
+    };
+}
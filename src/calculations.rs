@@ -1,6 +1,9 @@
+use std::str::FromStr;
 use log::{debug, info, warn};
-use crate::constant::{SUI_DECIMALS, USDC_DECIMALS};
-use crate::market::CalculationResult;
+use serde_derive::{Deserialize, Serialize};
+use crate::constant::{SUI_DECIMALS, SUI_USDC_DECIMALS, USDC_DECIMALS};
+use crate::stats::{MarketSnapshot, HOUR_MS};
+use crate::oracle::ValidatedPrice;
 
 #[derive(Debug)]
 pub struct Result {
@@ -11,11 +14,89 @@ pub struct Result {
     pub duration_ms: u64,
 }
 
+// which oracle price the quotes are centered on
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceAnchor {
+    Spot,
+    Ema,
+}
+
+impl FromStr for PriceAnchor {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "spot" => Ok(PriceAnchor::Spot),
+            "ema" => Ok(PriceAnchor::Ema),
+            other => Err(format!("unknown price anchor: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct QuoteOptions {
+    pub anchor: PriceAnchor,
+    // added spread (in percent) per 1 percent of conf/price
+    pub conf_multiplier: f64,
+    // fraction of momentum by which the anchor price is shifted
+    pub momentum_weight: f64,
+}
+
+impl Default for QuoteOptions {
+    fn default() -> Self {
+        return QuoteOptions{ anchor: PriceAnchor::Spot, conf_multiplier: 0.0, momentum_weight: 0.0 }
+    }
+}
+
+// relative gap between spot and ema, positive when spot is above ema
+pub fn momentum(price: &ValidatedPrice) -> f64 {
+    return (price.price - price.ema_price) / price.ema_price;
+}
+
+// price the quotes are centered on, shifted in direction of momentum
+pub fn anchor_price(price: &ValidatedPrice, options: &QuoteOptions) -> f64 {
+    let anchor = match options.anchor {
+        PriceAnchor::Spot => price.price,
+        PriceAnchor::Ema => price.ema_price,
+    };
+    return anchor * (1.0 + momentum(price) * options.momentum_weight);
+}
+
+// spread in percent added because of oracle uncertainty
+pub fn confidence_spread(price: &ValidatedPrice, options: &QuoteOptions) -> f64 {
+    let conf_ratio = match options.anchor {
+        PriceAnchor::Spot => price.conf / price.price,
+        PriceAnchor::Ema => price.ema_conf / price.ema_price,
+    };
+    return conf_ratio * 100.0 * options.conf_multiplier;
+}
+
+pub fn calculate_oracle_totals(inputs: &Vec<u64>, oracle_price: &ValidatedPrice, options: &QuoteOptions,
+                               base_decimals: Option<u64>, quote_decimals: Option<u64>, price_decimals: Option<u64>,
+                               stats: &MarketSnapshot,
+                               volatility: f64) -> Option<Result> {
+    let price = anchor_price(oracle_price, options);
+    let extra_spread = confidence_spread(oracle_price, options);
+    debug!("anchor price: {} momentum: {} confidence spread: {}", price, momentum(oracle_price), extra_spread);
+    return calculate_totals_with_spread(inputs, price, base_decimals, quote_decimals, price_decimals, stats, volatility, extra_spread);
+}
+
 pub fn calculate_totals(inputs: &Vec<u64>, price: f64, base_decimals: Option<u64>, quote_decimals: Option<u64>,
-                        calc: CalculationResult,
+                        stats: &MarketSnapshot,
                         volatility: f64) -> Option<Result> {
+    return calculate_totals_with_spread(inputs, price, base_decimals, quote_decimals, None, stats, volatility, 0.0);
+}
+
+// extra_spread is added to the volatility based spread, in percent,
+// price_decimals is deepbook price scaling, SUI_USDC_DECIMALS if not provided
+pub fn calculate_totals_with_spread(inputs: &Vec<u64>, price: f64, base_decimals: Option<u64>, quote_decimals: Option<u64>, price_decimals: Option<u64>,
+                        stats: &MarketSnapshot,
+                        volatility: f64,
+                        extra_spread: f64) -> Option<Result> {
     let b_dec = if base_decimals.is_some() {base_decimals.unwrap()} else {SUI_DECIMALS};
     let q_dec = if quote_decimals.is_some() {quote_decimals.unwrap()} else {USDC_DECIMALS};
+    let p_dec = (if price_decimals.is_some() {price_decimals.unwrap()} else {SUI_USDC_DECIMALS}) as f64;
     let base_amounts = ((inputs[0].clone() + inputs[1].clone()) as f64 * price) as u64 / b_dec;
     let quote_amounts = (inputs[2].clone() + inputs[3].clone()) / q_dec;
     let total = base_amounts + quote_amounts;
@@ -33,10 +114,14 @@ pub fn calculate_totals(inputs: &Vec<u64>, price: f64, base_decimals: Option<u64
         return None;
     }
     let mut spread = 0.015_f64;
-    let ratio = (calc.filled_total as f64)/(total as f64);
+    // traded in the last hour and per second of it, in whole quote units
+    let hour = stats.window(HOUR_MS);
+    let filled_total = if hour.is_some() {hour.unwrap().notional / q_dec as f64} else {0.0};
+    let filled_per_s = if hour.is_some() {hour.unwrap().notional_per_s() / q_dec as f64} else {0.0};
+    let ratio = filled_total/(total as f64);
     // decrease influence of volatility on spread
-    let decrease_vol = if calc.filled_per_s > total {ratio} else {1.0};
-    let spread = spread * ((volatility/(0.012 * decrease_vol)) + 1.0).min(20.0);
+    let decrease_vol = if filled_per_s > total as f64 {ratio} else {1.0};
+    let spread = spread * ((volatility/(0.012 * decrease_vol)) + 1.0).min(20.0) + extra_spread;
     // TODO decrease size depending on volatility ...
     let mut result = Result{
         ask_price: 0,
@@ -57,9 +142,9 @@ pub fn calculate_totals(inputs: &Vec<u64>, price: f64, base_decimals: Option<u64
     let raw_ask_quantity = if inputs[0] > 0 {((inputs[0])/ 100_000_000) * 100_000_000} else {0};
     let raw_bid_quantity = if quote_amounts > 0 {((((quote_amounts as f64)/ ask_price) as u64 * b_dec)/100_000_000) * 100_000_000} else {0};
     result.duration_ms = duration_final;
-    result.ask_price = ((ask_price * 1_000_000_f64) as u64/ 100) * 100;
+    result.ask_price = ((ask_price * p_dec) as u64/ 100) * 100;
     result.ask_quantity = if (base_amounts <= quote_amounts ||( base_quote_ratio < 1.2 &&  base_quote_ratio > 0.8)) {raw_ask_quantity} else {(((raw_ask_quantity - raw_bid_quantity)/2)/100_000_000) * 100_000_000 };
-    result.bid_price = ((bid_price * 1_000_000_f64) as u64/ 100) * 100;
+    result.bid_price = ((bid_price * p_dec) as u64/ 100) * 100;
     result.bid_quantity = if (quote_amounts <= base_amounts  || ( base_quote_ratio < 1.2 &&  base_quote_ratio > 0.8)) {raw_bid_quantity} else {(((raw_bid_quantity - raw_ask_quantity)/2)/100_000_000) * 100_000_000};
     info!("{:?} {}", result, spread);
     if result.bid_quantity == 0 && result.ask_quantity == 0 {
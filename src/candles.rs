@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+use std::fs;
+use serde_derive::{Deserialize, Serialize};
+use sui_sdk::rpc_types::SuiEvent;
+use sui_sdk::SuiClient;
+use sui_types::base_types::ObjectID;
+use crate::events::{decode_event, DeepBookEvent};
+use crate::market::query_fills_since;
+use crate::oracle::ValidatedPrice;
+use crate::volatility::OhlcBar;
+
+pub const CSV_HEADER: &str = "start_ms,interval_ms,open,high,low,close,volume,notional,count";
+
+// prices in quote per base, volume in base and notional in quote units, both 0 for oracle candles
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Candle {
+    pub start_ms: u64,
+    pub interval_ms: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub notional: f64,
+    // fills or price samples
+    pub count: u64,
+}
+
+impl Candle {
+    pub fn vwap(&self) -> Option<f64> {
+        return if self.volume > 0.0 {Some(self.notional / self.volume)} else {None};
+    }
+
+    pub fn bar(&self) -> OhlcBar {
+        return OhlcBar{ start_ms: self.start_ms, duration_ms: self.interval_ms, open: self.open, high: self.high, low: self.low, close: self.close };
+    }
+
+    pub fn csv_row(&self) -> String {
+        return format!("{},{},{},{},{},{},{},{},{}", self.start_ms, self.interval_ms, self.open, self.high, self.low, self.close, self.volume, self.notional, self.count);
+    }
+}
+
+pub fn bucket(timestamp_ms: u64, interval_ms: u64) -> u64 {
+    return timestamp_ms - timestamp_ms % interval_ms;
+}
+
+// candles with times of their first and last sample, so that backfill may come in any order
+struct CandleState {
+    candle: Candle,
+    open_ms: u64,
+    close_ms: u64,
+}
+
+// bars of one interval keyed by start, intervals without samples have no candle, only the last max_candles are kept
+pub struct CandleBuilder {
+    pub interval_ms: u64,
+    max_candles: usize,
+    candles: BTreeMap<u64, CandleState>,
+}
+
+impl CandleBuilder {
+    pub fn new(interval_ms: u64, max_candles: usize) -> CandleBuilder {
+        return CandleBuilder{ interval_ms: interval_ms.max(1), max_candles, candles: BTreeMap::new() }
+    }
+
+    pub fn add(&mut self, timestamp_ms: u64, price: f64, volume: f64) {
+        let start_ms = bucket(timestamp_ms, self.interval_ms);
+        let state = self.candles.entry(start_ms).or_insert(CandleState{
+            candle: Candle{ start_ms, interval_ms: self.interval_ms, open: price, high: price, low: price, close: price, volume: 0.0, notional: 0.0, count: 0 },
+            open_ms: timestamp_ms,
+            close_ms: timestamp_ms,
+        });
+        let candle = &mut state.candle;
+        candle.high = candle.high.max(price);
+        candle.low = candle.low.min(price);
+        candle.volume += volume;
+        candle.notional += price * volume;
+        candle.count += 1;
+        if timestamp_ms < state.open_ms {
+            state.open_ms = timestamp_ms;
+            candle.open = price;
+        }
+        if timestamp_ms >= state.close_ms {
+            state.close_ms = timestamp_ms;
+            candle.close = price;
+        }
+        while self.candles.len() > self.max_candles {
+            let first = *self.candles.keys().next().unwrap();
+            self.candles.remove(&first);
+        }
+    }
+
+    // OrderFilled of the pool, price_decimals and base_decimals as in PoolConfig, true if added
+    pub fn add_fill(&mut self, event: &SuiEvent, pool_id: ObjectID, price_decimals: u64, base_decimals: u64) -> bool {
+        let decoded = decode_event(event);
+        if decoded.is_none() {
+            return false;
+        }
+        return match decoded.unwrap().event {
+            DeepBookEvent::OrderFilled(x) if x.pool_id == pool_id => {
+                let price = x.price as f64 / price_decimals as f64;
+                let volume = x.base_asset_quantity_filled as f64 / base_decimals as f64;
+                self.add(event.timestamp_ms.unwrap_or(0), price, volume);
+                true
+            }
+            _ => false,
+        };
+    }
+
+    // pyth price at its publish time
+    pub fn add_price(&mut self, price: &ValidatedPrice) {
+        self.add(price.publish_time as u64 * 1000, price.price, 0.0);
+    }
+
+    // oldest first
+    pub fn candles(&self) -> Vec<Candle> {
+        return self.candles.values().map(|x| x.candle).collect();
+    }
+
+    // candles whose interval ended by now_ms
+    pub fn closed(&self, now_ms: u64) -> Vec<Candle> {
+        return self.candles.values().map(|x| x.candle).filter(|x| x.start_ms + x.interval_ms <= now_ms).collect();
+    }
+}
+
+// adds OrderFilled events of the pool since since_ms from query_events pages, returns number of fills
+pub async fn backfill_fills(client: &SuiClient, builder: &mut CandleBuilder, pool_id: ObjectID, base_asset: String, quote_asset: String,
+                            since_ms: u64, price_decimals: u64, base_decimals: u64) -> usize {
+    let events = query_fills_since(client, pool_id, base_asset, quote_asset, since_ms).await;
+    return events.iter().filter(|x| builder.add_fill(x, pool_id, price_decimals, base_decimals)).count();
+}
+
+pub fn to_csv(candles: &Vec<Candle>) -> String {
+    let mut lines = vec![CSV_HEADER.to_string()];
+    lines.extend(candles.iter().map(|x| x.csv_row()));
+    return lines.join("\n") + "\n";
+}
+
+pub fn write_csv(path: &str, candles: &Vec<Candle>) {
+    fs::write(path, to_csv(candles)).expect("could not write candles csv");
+}
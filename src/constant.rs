@@ -9,9 +9,15 @@ pub const LIMIT_ORDER_IMMEDIATE_OR_CANCEL: u8 = 1;
 pub const LIMIT_ORDER_FILL_OR_KILL: u8 = 2;
 pub const LIMIT_ORDER_POST_OR_ABORT: u8 = 3;
 pub const DEEPBOOK_PKG: &str = "0x000000000000000000000000000000000000000000000000000000000000dee9";
+// deepbook prices are quote units per base unit scaled by 1e9
+pub const FLOAT_SCALING: u64 = 1_000_000_000;
 pub const HERMES_LATES_PRICE_FEEDS: &str = "https://hermes.pyth.network/api/latest_price_feeds"; // ?ids[]=0x23d7315113f5b1d3ba7a83604c44b94d79f4fd69af77f804fc7f920a6dc65744
 pub const SUI_PRICE_FEED: &str = "0x23d7315113f5b1d3ba7a83604c44b94d79f4fd69af77f804fc7f920a6dc65744";
 pub const USDC_PRICE_FEED: &str = "0xeaa020c61cc479712813461ce153894a96a6c00b21ed0cfc2798d1f9a9e9c94a";
+pub const SUI_TYPE: &str = "0x2::sui::SUI";
+// wormhole usdc
+pub const USDC_TYPE: &str = "0x5d4b302506645c37ff133b98c4b50a5ae14841659738d6d733d59d0d217a93bf::coin::COIN";
+pub const SUI_USDC_POOL: &str = "0x4405b50d791fd3346754e8171aaab6bc2ed26c2c46efdd033c14b30ae507ac33";
 
 /*
 /// Returns (base quantity filled, quote quantity filled, whether a maker order is being placed, order id of the maker order)
@@ -0,0 +1,249 @@
+use std::str::FromStr;
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use sui_sdk::rpc_types::SuiEvent;
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::{StructTag, TypeTag};
+use crate::constant::DEEPBOOK_PKG;
+
+// u64 is a decimal string in event json and a plain u64 in bcs
+pub mod u64_string {
+    use std::str::FromStr;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            return serializer.serialize_str(&value.to_string());
+        }
+        return serializer.serialize_u64(*value);
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        if !deserializer.is_human_readable() {
+            return u64::deserialize(deserializer);
+        }
+        #[derive(serde_derive::Deserialize)]
+        #[serde(untagged)]
+        enum StringOrNumber {
+            String(String),
+            Number(u64),
+        }
+        return match StringOrNumber::deserialize(deserializer)? {
+            StringOrNumber::String(x) => u64::from_str(&x).map_err(de::Error::custom),
+            StringOrNumber::Number(x) => Ok(x),
+        };
+    }
+}
+
+// std::type_name::TypeName, name is without 0x i.e. 0000..0002::sui::SUI
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TypeName {
+    pub name: String,
+}
+
+// clob_v2::PoolCreated, the only event without type parameters
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PoolCreatedEvent {
+    pub pool_id: ObjectID,
+    pub base_asset: TypeName,
+    pub quote_asset: TypeName,
+    #[serde(with = "u64_string")]
+    pub taker_fee_rate: u64,
+    #[serde(with = "u64_string")]
+    pub maker_rebate_rate: u64,
+    #[serde(with = "u64_string")]
+    pub tick_size: u64,
+    #[serde(with = "u64_string")]
+    pub lot_size: u64,
+}
+
+// clob_v2::OrderPlaced<BaseAsset, QuoteAsset>
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OrderPlacedEvent {
+    pub pool_id: ObjectID,
+    #[serde(with = "u64_string")]
+    pub order_id: u64,
+    #[serde(with = "u64_string")]
+    pub client_order_id: u64,
+    pub is_bid: bool,
+    pub owner: SuiAddress,
+    #[serde(with = "u64_string")]
+    pub original_quantity: u64,
+    #[serde(with = "u64_string")]
+    pub base_asset_quantity_placed: u64,
+    #[serde(with = "u64_string")]
+    pub price: u64,
+    #[serde(with = "u64_string")]
+    pub expire_timestamp: u64,
+}
+
+// clob_v2::OrderCanceled<BaseAsset, QuoteAsset>
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OrderCanceledEvent {
+    pub pool_id: ObjectID,
+    #[serde(with = "u64_string")]
+    pub order_id: u64,
+    #[serde(with = "u64_string")]
+    pub client_order_id: u64,
+    pub is_bid: bool,
+    pub owner: SuiAddress,
+    #[serde(with = "u64_string")]
+    pub original_quantity: u64,
+    #[serde(with = "u64_string")]
+    pub base_asset_quantity_canceled: u64,
+    #[serde(with = "u64_string")]
+    pub price: u64,
+}
+
+// clob_v2::AllOrdersCanceledComponent<BaseAsset, QuoteAsset>
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CanceledOrder {
+    #[serde(with = "u64_string")]
+    pub client_order_id: u64,
+    #[serde(with = "u64_string")]
+    pub order_id: u64,
+    pub is_bid: bool,
+    pub owner: SuiAddress,
+    #[serde(with = "u64_string")]
+    pub original_quantity: u64,
+    #[serde(with = "u64_string")]
+    pub base_asset_quantity_canceled: u64,
+    #[serde(with = "u64_string")]
+    pub price: u64,
+}
+
+// clob_v2::AllOrdersCanceled<BaseAsset, QuoteAsset>
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AllOrdersCanceledEvent {
+    pub pool_id: ObjectID,
+    pub orders_canceled: Vec<CanceledOrder>,
+}
+
+// clob_v2::OrderFilled<BaseAsset, QuoteAsset>, is_bid is the side of the maker order,
+// maker_address and taker_address are owners of account caps
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OrderFilledEvent {
+    pub pool_id: ObjectID,
+    #[serde(with = "u64_string")]
+    pub order_id: u64,
+    #[serde(with = "u64_string")]
+    pub taker_client_order_id: u64,
+    #[serde(with = "u64_string")]
+    pub maker_client_order_id: u64,
+    #[serde(with = "u64_string")]
+    pub original_quantity: u64,
+    #[serde(with = "u64_string")]
+    pub base_asset_quantity_filled: u64,
+    #[serde(with = "u64_string")]
+    pub base_asset_quantity_remaining: u64,
+    pub is_bid: bool,
+    pub maker_address: SuiAddress,
+    pub taker_address: SuiAddress,
+    #[serde(with = "u64_string")]
+    pub price: u64,
+    #[serde(with = "u64_string")]
+    pub taker_commission: u64,
+    #[serde(with = "u64_string")]
+    pub maker_rebates: u64,
+}
+
+// clob_v2::DepositAsset<Asset> and clob_v2::WithdrawAsset<Asset>
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AssetEvent {
+    pub pool_id: ObjectID,
+    #[serde(with = "u64_string")]
+    pub quantity: u64,
+    pub owner: SuiAddress,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeepBookEvent {
+    PoolCreated(PoolCreatedEvent),
+    OrderPlaced(OrderPlacedEvent),
+    OrderCanceled(OrderCanceledEvent),
+    AllOrdersCanceled(AllOrdersCanceledEvent),
+    OrderFilled(OrderFilledEvent),
+    DepositAsset(AssetEvent),
+    WithdrawAsset(AssetEvent),
+}
+
+impl DeepBookEvent {
+    pub fn pool_id(&self) -> ObjectID {
+        return match self {
+            DeepBookEvent::PoolCreated(x) => x.pool_id,
+            DeepBookEvent::OrderPlaced(x) => x.pool_id,
+            DeepBookEvent::OrderCanceled(x) => x.pool_id,
+            DeepBookEvent::AllOrdersCanceled(x) => x.pool_id,
+            DeepBookEvent::OrderFilled(x) => x.pool_id,
+            DeepBookEvent::DepositAsset(x) => x.pool_id,
+            DeepBookEvent::WithdrawAsset(x) => x.pool_id,
+        };
+    }
+}
+
+// decoded event with type arguments of its struct, [base, quote] for order events, [asset] for deposit and withdraw
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedEvent {
+    pub type_params: Vec<TypeTag>,
+    pub event: DeepBookEvent,
+}
+
+impl TypedEvent {
+    pub fn base_asset(&self) -> Option<&TypeTag> {
+        return if self.type_params.len() == 2 {self.type_params.first()} else {None};
+    }
+
+    pub fn quote_asset(&self) -> Option<&TypeTag> {
+        return if self.type_params.len() == 2 {self.type_params.last()} else {None};
+    }
+}
+
+// parsed_json or bcs bytes of an event
+pub enum Payload<'a> {
+    Json(&'a Value),
+    Bcs(&'a [u8]),
+}
+
+fn parse<T: DeserializeOwned>(payload: &Payload) -> Option<T> {
+    let parsed = match payload {
+        Payload::Json(x) => serde_json::from_value::<T>((*x).clone()).map_err(|e| e.to_string()),
+        Payload::Bcs(x) => bcs::from_bytes::<T>(x).map_err(|e| e.to_string()),
+    };
+    if parsed.is_err() {
+        warn!("could not decode deepbook event: {:?}", parsed.err());
+        return None;
+    }
+    return parsed.ok();
+}
+
+pub fn is_clob_v2(type_: &StructTag) -> bool {
+    return ObjectID::from(type_.address) == ObjectID::from_str(DEEPBOOK_PKG).unwrap() && type_.module.as_str() == "clob_v2";
+}
+
+// None for events of other modules and unknown clob_v2 events
+pub fn decode(type_: &StructTag, payload: Payload) -> Option<TypedEvent> {
+    if !is_clob_v2(type_) {
+        return None;
+    }
+    let event = match type_.name.as_str() {
+        "PoolCreated" => DeepBookEvent::PoolCreated(parse(&payload)?),
+        "OrderPlaced" => DeepBookEvent::OrderPlaced(parse(&payload)?),
+        "OrderCanceled" => DeepBookEvent::OrderCanceled(parse(&payload)?),
+        "AllOrdersCanceled" => DeepBookEvent::AllOrdersCanceled(parse(&payload)?),
+        "OrderFilled" => DeepBookEvent::OrderFilled(parse(&payload)?),
+        "DepositAsset" => DeepBookEvent::DepositAsset(parse(&payload)?),
+        "WithdrawAsset" => DeepBookEvent::WithdrawAsset(parse(&payload)?),
+        _ => return None,
+    };
+    return Some(TypedEvent{ type_params: type_.type_params.clone(), event });
+}
+
+// bcs is exact, parsed_json is used when bcs is not returned
+pub fn decode_event(event: &SuiEvent) -> Option<TypedEvent> {
+    if event.bcs.len() > 0 {
+        return decode(&event.type_, Payload::Bcs(&event.bcs));
+    }
+    return decode(&event.type_, Payload::Json(&event.parsed_json));
+}
@@ -0,0 +1,108 @@
+use std::fs;
+use std::path::Path;
+use log::{debug, warn};
+use sui_sdk::rpc_types::{EventFilter, SuiEvent};
+use sui_sdk::SuiClient;
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::event::EventID;
+use crate::events::OrderFilledEvent;
+
+#[derive(Debug, Clone)]
+pub struct OwnFill {
+    pub id: EventID,
+    pub timestamp_ms: u64,
+    pub event: OrderFilledEvent,
+}
+
+pub fn load_cursor(path: &str) -> Option<EventID> {
+    if !Path::new(path).exists() {
+        return None;
+    }
+    let content = fs::read_to_string(path).expect("could not read fills cursor");
+    return serde_json::from_str(&content).expect("could not parse fills cursor");
+}
+
+pub fn save_cursor(path: &str, cursor: &EventID) {
+    fs::write(path, serde_json::to_string(cursor).unwrap()).expect("could not write fills cursor");
+}
+
+// OrderFilled of pool where maker or taker is one of owners
+pub fn own_fill(event: &SuiEvent, pool_id: ObjectID, owners: &Vec<SuiAddress>) -> Option<OwnFill> {
+    let parsed = serde_json::from_value::<OrderFilledEvent>(event.parsed_json.clone());
+    if parsed.is_err() {
+        warn!("unexpected OrderFilled event {:?}: {:?}", event.id, parsed.err());
+        return None;
+    }
+    let parsed = parsed.unwrap();
+    // events of all pools with the same assets share the type
+    if parsed.pool_id != pool_id || !(owners.contains(&parsed.maker_address) || owners.contains(&parsed.taker_address)) {
+        return None;
+    }
+    return Some(OwnFill{ id: event.id, timestamp_ms: event.timestamp_ms.unwrap_or(0), event: parsed });
+}
+
+// new own fills of one pool in order, the cursor is the last seen event, without saved cursor it starts at the most recent event
+pub struct FillStream<'a> {
+    client: &'a SuiClient,
+    event_type: String,
+    pool_id: ObjectID,
+    owners: Vec<SuiAddress>,
+    cursor: Option<EventID>,
+    started: bool,
+}
+
+impl FillStream<'_> {
+    // owners are owners of account caps, for a master cap the owner is the cap id, cursor is the last processed event
+    pub fn new(client: &SuiClient, pool_id: ObjectID, base_asset: String, quote_asset: String, owners: Vec<SuiAddress>, cursor: Option<EventID>) -> FillStream {
+        return FillStream{
+            client,
+            event_type: format!("0xdee9::clob_v2::OrderFilled<{base_asset}, {quote_asset}>"),
+            pool_id,
+            owners,
+            cursor,
+            started: cursor.is_some(),
+        }
+    }
+
+    pub fn cursor(&self) -> Option<EventID> {
+        return self.cursor;
+    }
+
+    // fills after the cursor, oldest first, the cursor moves past every fetched event
+    pub async fn poll(&mut self) -> Vec<OwnFill> {
+        let filter = EventFilter::MoveEventType(self.event_type.parse().unwrap());
+        let mut fills = vec![];
+        if !self.started {
+            let latest = self.client.event_api().query_events(filter, None, Some(1), true).await;
+            if latest.is_err() {
+                warn!("could not fetch latest fill: {:?}", latest.err());
+                return fills;
+            }
+            self.cursor = latest.unwrap().data.first().map(|x| x.id);
+            self.started = true;
+            return fills;
+        }
+        loop {
+            let page = self.client.event_api().query_events(filter.clone(), self.cursor, None, false).await;
+            if page.is_err() {
+                warn!("could not fetch fills: {:?}", page.err());
+                break;
+            }
+            let page = page.unwrap();
+            for event in page.data.iter() {
+                let fill = own_fill(event, self.pool_id, &self.owners);
+                if fill.is_some() {
+                    fills.push(fill.unwrap());
+                }
+            }
+            if page.data.len() > 0 {
+                self.cursor = Some(page.data.last().unwrap().id);
+            }
+            if !page.has_next_page || page.data.len() == 0 {
+                break;
+            }
+        }
+        debug!("{} new fills, cursor {:?}", fills.len(), self.cursor);
+        return fills;
+    }
+}
@@ -0,0 +1,191 @@
+use log::{debug, info, warn};
+use sui_sdk::rpc_types::{SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse};
+use sui_sdk::SuiClient;
+use sui_types::base_types::{ObjectID, ObjectRef, SuiAddress};
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::transaction::{Argument, Command, ObjectArg, ProgrammableTransaction};
+use tokio::sync::Mutex;
+use crate::constant::SUI_TYPE;
+use crate::transaction::TransactionWrapper;
+
+// max number of coins merged in one transaction
+const MAX_MERGE: usize = 200;
+// free dust coins that make maintain merge them again
+pub const DUST_MERGE_COUNT: usize = 10;
+
+#[derive(Debug, Clone)]
+pub struct PooledCoin {
+    pub object_ref: ObjectRef,
+    pub balance: u64,
+    // used as gas by a transaction in flight
+    pub reserved: bool,
+}
+
+// first free coin with at least min_balance
+pub fn select_coin(coins: &Vec<PooledCoin>, min_balance: u64) -> Option<usize> {
+    return coins.iter().position(|x| !x.reserved && x.balance >= min_balance);
+}
+
+// free coin with the highest balance if it has at least min_balance
+pub fn largest_coin(coins: &Vec<PooledCoin>, min_balance: u64) -> Option<usize> {
+    let index = coins.iter().enumerate().filter(|(_, x)| !x.reserved).max_by_key(|(_, x)| x.balance).map(|(i, _)| i)?;
+    return if coins[index].balance >= min_balance {Some(index)} else {None};
+}
+
+// free coins below dust_threshold
+pub fn dust_count(coins: &Vec<PooledCoin>, dust_threshold: u64) -> usize {
+    return coins.iter().filter(|x| !x.reserved && x.balance < dust_threshold).count();
+}
+
+// new gas coin ref and net gas used from effects of executed transaction
+pub fn gas_update(response: &SuiTransactionBlockResponse) -> Option<(ObjectRef, i64)> {
+    let effects = response.effects.as_ref()?;
+    return Some((effects.gas_object().reference.to_object_ref(), effects.gas_cost_summary().net_gas_usage()));
+}
+
+// merges dust into gas coin and splits count coins of amount from it, all sent to owner
+pub fn merge_and_split_transaction(owner: SuiAddress, dust: &Vec<ObjectRef>, count: usize, amount: u64) -> ProgrammableTransaction {
+    let mut tb = ProgrammableTransactionBuilder::new();
+    if dust.len() > 0 {
+        let coins: Vec<Argument> = dust.iter().map(|x| tb.obj(ObjectArg::ImmOrOwnedObject(*x)).unwrap()).collect();
+        tb.command(Command::MergeCoins(Argument::GasCoin, coins));
+    }
+    if count > 0 {
+        tb.pay_sui(vec![owner; count], vec![amount; count]).unwrap();
+    }
+    return tb.finish();
+}
+
+// gas coins of one address, every transaction in flight gets its own coin so they never equivocate
+pub struct GasPool<'a> {
+    client: &'a SuiClient,
+    owner: SuiAddress,
+    coins: Mutex<Vec<PooledCoin>>,
+    // free coins below are merged back by prepare
+    pub dust_threshold: u64,
+    // count, amount and gas budget of the last prepare, used again by maintain
+    target: Mutex<Option<(usize, u64, u64)>>,
+}
+
+impl GasPool<'_> {
+    pub fn new(client: &SuiClient, owner: SuiAddress, dust_threshold: u64) -> GasPool {
+        return GasPool{ client, owner, coins: Mutex::new(vec![]), dust_threshold, target: Mutex::new(None) }
+    }
+
+    // fetches all SUI coins of owner, reserved coins stay reserved
+    pub async fn refresh(&self) {
+        let mut fetched = vec![];
+        let mut cursor = None;
+        loop {
+            let page = self.client.coin_read_api().get_coins(self.owner, Some(SUI_TYPE.to_string()), cursor, None).await;
+            if page.is_err() {
+                warn!("could not fetch gas coins: {:?}", page);
+                return;
+            }
+            let page = page.unwrap();
+            for coin in page.data.iter() {
+                fetched.push(PooledCoin{ object_ref: coin.object_ref(), balance: coin.balance, reserved: false });
+            }
+            if !page.has_next_page || page.next_cursor.is_none() {
+                break;
+            }
+            cursor = page.next_cursor;
+        }
+        let mut coins = self.coins.lock().await;
+        for coin in fetched.iter_mut() {
+            let previous = coins.iter().find(|x| x.object_ref.0 == coin.object_ref.0);
+            if previous.is_some() && previous.unwrap().reserved {
+                *coin = previous.unwrap().clone();
+            }
+        }
+        debug!("gas coins: {:?}", fetched);
+        *coins = fetched;
+    }
+
+    pub async fn reserve(&self, min_balance: u64) -> Option<ObjectRef> {
+        return self.reserve_with(min_balance, select_coin).await;
+    }
+
+    // reserves the free coin with the highest balance
+    pub async fn reserve_largest(&self, min_balance: u64) -> Option<ObjectRef> {
+        return self.reserve_with(min_balance, largest_coin).await;
+    }
+
+    async fn reserve_with(&self, min_balance: u64, select: fn(&Vec<PooledCoin>, u64) -> Option<usize>) -> Option<ObjectRef> {
+        let mut coins = self.coins.lock().await;
+        let index = select(&coins, min_balance);
+        if index.is_none() {
+            warn!("no free gas coin with balance {}", min_balance);
+            return None;
+        }
+        coins[index.unwrap()].reserved = true;
+        return Some(coins[index.unwrap()].object_ref);
+    }
+
+    // frees the coin with version from effects, without effects the coin state is unknown and all coins are refetched
+    pub async fn release(&self, coin_id: ObjectID, response: Option<&SuiTransactionBlockResponse>) {
+        let update = response.and_then(|x| gas_update(x));
+        if update.is_none() {
+            {
+                let mut coins = self.coins.lock().await;
+                let coin = coins.iter_mut().find(|x| x.object_ref.0 == coin_id);
+                if coin.is_some() {
+                    coin.unwrap().reserved = false;
+                }
+            }
+            self.refresh().await;
+            return;
+        }
+        let (object_ref, gas_used) = update.unwrap();
+        let mut coins = self.coins.lock().await;
+        let coin = coins.iter_mut().find(|x| x.object_ref.0 == coin_id);
+        if coin.is_some() {
+            let coin = coin.unwrap();
+            coin.object_ref = object_ref;
+            coin.balance = (coin.balance as i64 - gas_used).max(0) as u64;
+            coin.reserved = false;
+        }
+    }
+
+    pub async fn coins(&self) -> Vec<PooledCoin> {
+        return self.coins.lock().await.clone();
+    }
+
+    // merges free dust coins and splits the biggest coin so that there are at least count free coins of amount
+    pub async fn prepare(&self, transaction_wrapper: &TransactionWrapper<'_>, count: usize, amount: u64, gas_budget: u64) {
+        *self.target.lock().await = Some((count, amount, gas_budget));
+        self.refresh().await;
+        let coins = self.coins().await;
+        let usable = coins.iter().filter(|x| !x.reserved && x.balance >= amount).count();
+        let mut dust: Vec<PooledCoin> = coins.iter().filter(|x| !x.reserved && x.balance < self.dust_threshold).cloned().collect();
+        dust.truncate(MAX_MERGE);
+        // the biggest coin pays for the split and keeps the rest
+        let missing = count.saturating_sub(usable);
+        if missing == 0 && dust.len() < 2 {
+            return;
+        }
+        let dust_balance: u64 = dust.iter().map(|x| x.balance).sum();
+        let gas = self.reserve_largest(missing as u64 * amount + gas_budget.saturating_sub(dust_balance)).await;
+        if gas.is_none() {
+            warn!("not enough SUI to split {} gas coins of {}", missing, amount);
+            return;
+        }
+        let gas = gas.unwrap();
+        let dust_refs: Vec<ObjectRef> = dust.iter().map(|x| x.object_ref).filter(|x| x.0 != gas.0).collect();
+        let ptx = merge_and_split_transaction(self.owner, &dust_refs, missing, amount);
+        let response = transaction_wrapper.execute_ptx(ptx, Some(gas), None, Some(gas_budget)).await;
+        info!("merged {} dust coins, split {} gas coins: {:?}", dust_refs.len(), missing, response.as_ref().map(|x| x.digest));
+        self.release(gas.0, None).await;
+    }
+
+    // gas paid by every transaction turns pool coins into dust over a long run, once there are DUST_MERGE_COUNT
+    // free dust coins prepare runs again to merge them and split new coins, not called while transactions are in flight
+    pub async fn maintain(&self, transaction_wrapper: &TransactionWrapper<'_>) {
+        let target = *self.target.lock().await;
+        if target.is_none() || dust_count(&self.coins().await, self.dust_threshold) < DUST_MERGE_COUNT {
+            return;
+        }
+        let (count, amount, gas_budget) = target.unwrap();
+        self.prepare(transaction_wrapper, count, amount, gas_budget).await;
+    }
+}
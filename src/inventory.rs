@@ -0,0 +1,132 @@
+use log::{info, warn};
+use serde_derive::{Deserialize, Serialize};
+use sui_sdk::SuiClient;
+use sui_types::base_types::SuiAddress;
+use crate::calculations::Result as Quotes;
+use crate::risk::OrderRequest;
+
+// limits of base asset inventory, either in raw base units or as fraction of total value in base,
+// soft limits stop quoting the side which adds inventory, hard limits flatten with IOC order
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct InventoryConfig {
+    #[serde(default)]
+    pub fraction: bool,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub hard_min: Option<f64>,
+    pub hard_max: Option<f64>,
+    // flatten order price is this far through the oracle price
+    #[serde(default)]
+    pub flatten_slippage: f64,
+}
+
+// custodian (available and locked) plus wallet balances in raw units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Inventory {
+    pub base: u64,
+    pub quote: u64,
+}
+
+impl Inventory {
+    // balance_data as returned by OrderWrapper::get_data
+    pub fn new(balance_data: &Vec<u64>, wallet_base: u64, wallet_quote: u64) -> Inventory {
+        return Inventory{
+            base: balance_data[0] + balance_data[1] + wallet_base,
+            quote: balance_data[2] + balance_data[3] + wallet_quote,
+        }
+    }
+
+    // total value in raw base units, price is quote per base
+    pub fn total_in_base(&self, price: f64, base_decimals: u64, quote_decimals: u64) -> f64 {
+        let quote_in_base = (self.quote as f64 / quote_decimals as f64) / price * base_decimals as f64;
+        return self.base as f64 + quote_in_base;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InventoryState {
+    Normal,
+    // above soft max, only ask is quoted
+    Long,
+    // below soft min, only bid is quoted
+    Short,
+    // above hard max, quantity of base to sell
+    HardLong(u64),
+    // below hard min, quantity of base to buy
+    HardShort(u64),
+}
+
+pub fn inventory_state(inventory: &Inventory, price: f64, base_decimals: u64, quote_decimals: u64, config: &InventoryConfig) -> InventoryState {
+    let total = inventory.total_in_base(price, base_decimals, quote_decimals);
+    // raw base units per unit of measure
+    let scale = if config.fraction {total} else {1.0};
+    if scale <= 0.0 {
+        return InventoryState::Normal;
+    }
+    let measure = inventory.base as f64 / scale;
+    if config.hard_max.is_some() && measure > config.hard_max.unwrap() {
+        let target = if config.max.is_some() {config.max.unwrap()} else {config.hard_max.unwrap()};
+        return InventoryState::HardLong(((measure - target) * scale) as u64);
+    }
+    if config.hard_min.is_some() && measure < config.hard_min.unwrap() {
+        let target = if config.min.is_some() {config.min.unwrap()} else {config.hard_min.unwrap()};
+        return InventoryState::HardShort(((target - measure) * scale) as u64);
+    }
+    if config.max.is_some() && measure > config.max.unwrap() {
+        return InventoryState::Long;
+    }
+    if config.min.is_some() && measure < config.min.unwrap() {
+        return InventoryState::Short;
+    }
+    return InventoryState::Normal;
+}
+
+// removes the side which would move inventory further from limits
+pub fn apply_inventory_limits(quotes: &mut Quotes, state: InventoryState) {
+    match state {
+        InventoryState::Long | InventoryState::HardLong(_) => quotes.bid_quantity = 0,
+        InventoryState::Short | InventoryState::HardShort(_) => quotes.ask_quantity = 0,
+        InventoryState::Normal => {}
+    }
+}
+
+// IOC order bringing inventory back to soft limit, quantity rounded down to lot_size and capped by available custodian balance,
+// price is oracle price moved by slippage in deepbook units rounded to tick_size
+pub fn flatten_order(state: InventoryState, balance_data: &Vec<u64>, price: f64, price_decimals: u64, base_decimals: u64,
+                     quote_decimals: u64, tick_size: u64, lot_size: u64, slippage: f64) -> Option<OrderRequest> {
+    let tick = tick_size.max(1);
+    let lot = lot_size.max(1);
+    let (is_bid, quantity, limit) = match state {
+        InventoryState::HardLong(excess) => {
+            let limit = ((price * (1.0 - slippage) * price_decimals as f64) as u64 / tick) * tick;
+            (false, excess.min(balance_data[0]), limit)
+        }
+        InventoryState::HardShort(shortfall) => {
+            let limit = (((price * (1.0 + slippage) * price_decimals as f64) as u64 + tick - 1) / tick) * tick;
+            // quote available limits how much base can be bought
+            let affordable = ((balance_data[2] as f64 / quote_decimals as f64) / (price * (1.0 + slippage)) * base_decimals as f64) as u64;
+            (true, shortfall.min(affordable), limit)
+        }
+        _ => return None,
+    };
+    let quantity = (quantity / lot) * lot;
+    if quantity == 0 || limit == 0 {
+        warn!("inventory {:?} cannot be flattened from custodian balances {:?}", state, balance_data);
+        return None;
+    }
+    info!("flatten inventory {:?} with {} {} at {}", state, if is_bid {"bid"} else {"ask"}, quantity, limit);
+    return Some(OrderRequest{ price: limit, quantity, is_bid });
+}
+
+// wallet balances of base and quote coin types in raw units
+pub async fn wallet_balances(client: &SuiClient, owner: SuiAddress, base_asset: &str, quote_asset: &str) -> (u64, u64) {
+    let base = client.coin_read_api().get_balance(owner, Some(base_asset.to_string())).await;
+    let quote = client.coin_read_api().get_balance(owner, Some(quote_asset.to_string())).await;
+    if base.is_err() || quote.is_err() {
+        warn!("could not fetch wallet balances: {:?} {:?}", base, quote);
+    }
+    return (
+        base.map(|x| x.total_balance as u64).unwrap_or(0),
+        quote.map(|x| x.total_balance as u64).unwrap_or(0),
+    );
+}
@@ -7,4 +7,20 @@ pub mod pyth;
 pub mod pool;
 pub mod volatility;
 pub mod transaction;
-pub mod calculations;
\ No newline at end of file
+pub mod calculations;
+pub mod oracle;
+pub mod source;
+pub mod pyth_onchain;
+pub mod runner;
+pub mod gas;
+pub mod signer;
+pub mod account;
+pub mod risk;
+pub mod inventory;
+pub mod breaker;
+pub mod pnl;
+pub mod events;
+pub mod fills;
+pub mod subscriber;
+pub mod stats;
+pub mod candles;
\ No newline at end of file
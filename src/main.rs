@@ -1,48 +1,517 @@
 
 use std::str::FromStr;
-use log::{debug, info, LevelFilter};
+use log::{debug, info, LevelFilter, warn};
 use sui_keys::keystore::{AccountKeystore, InMemKeystore};
-use sui_sdk::SuiClientBuilder;
+use sui_sdk::{SuiClient, SuiClientBuilder};
 use sui_types::base_types::{ObjectID, SuiAddress};
 use sui_types::crypto::SignatureScheme;
 use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use sui_types::transaction::{Transaction, TransactionData, TransactionKind};
 use sui_types::TypeTag;
 use std::env;
+use std::path::Path;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use sui_sdk::rpc_types::{SuiObjectDataOptions, SuiTransactionBlockResponseOptions, SuiTypeTag};
+use sui_sdk::rpc_types::{SuiObjectDataOptions, SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions, SuiTypeTag};
 use sui_types::quorum_driver_types::ExecuteTransactionRequestType;
 use shared_crypto::intent::Intent;
-use sui_mm::constant::{LIMIT_ORDER_NO_RESTRICTION, LIMIT_ORDER_POST_OR_ABORT};
-use sui_mm::market::{CalculationResult, get_fills, get_market_price};
+use sui_mm::constant::{LIMIT_ORDER_FILL_OR_KILL, LIMIT_ORDER_IMMEDIATE_OR_CANCEL, LIMIT_ORDER_NO_RESTRICTION, LIMIT_ORDER_POST_OR_ABORT, SUI_PRICE_FEED, SUI_TYPE, SUI_USDC_POOL, USDC_PRICE_FEED};
+use sui_mm::pool::{get_pool_params, normalize_type, pool_decimals, sort_summaries, summarize_pool, PoolRegistry, PoolSort};
+use sui_mm::risk::{RiskChecker, RiskConfig};
+use sui_mm::market::{get_market_price, query_fills_since};
 use sui_mm::order::{Order, OrderWrapper};
-use sui_mm::pyth::{get_sui_usdc_price, PythFeeder};
-use sui_mm::user::{get_account_balance, get_account_cap, parse_result_account_balance};
+use sui_mm::user::{create_account, create_child_account, get_account_balance, get_account_cap_owner, get_all_account_caps, parse_result_account_balance, select_coin};
+use sui_mm::account::AccountNames;
 use sui_mm::utils::{parse_result_u64, parse_result_u64_from_vec};
 use bcs::from_bytes;
 use tokio::sync::Mutex;
 use tokio::time::sleep;
-use sui_mm::transaction::TransactionWrapper;
-use sui_mm::volatility::Volatility;
-use clap::Parser;
+use sui_mm::transaction::{GasConfig, SubmitConfig, TransactionWrapper};
+use sui_mm::breaker::{BreakerConfig, CircuitBreaker, KillSwitch};
+use sui_mm::gas::GasPool;
+use sui_mm::fills::own_fill;
+use sui_mm::pnl::{day_of, own_fills, PnlEngine, DAY_MS};
+use sui_mm::inventory::InventoryConfig;
+use sui_mm::signer::{parse_scheme, KeystoreSigner, PrivateKeySigner, RemoteSigner, Signer};
+use sui_mm::volatility::{ParkinsonVolatility, VolatilityEstimator};
+use clap::{Args, Parser, Subcommand};
 use clap::ArgAction;
 use statistical::mean;
-use sui_mm::calculations::calculate_totals;
+use sui_mm::calculations::PriceAnchor;
+use sui_mm::oracle::{FeedPair, OracleConfig, PriceOracle, ValidatedPrice};
+use sui_mm::runner::{apply_inventory, cancel_all_pools, decide, fetch_inventory, load_pool_configs, log_submit_result, pnl_path, pool_aggregator, pool_snapshot, run_pools, submit_action, PoolConfig, PoolPnl, QuoteAction, QuoteVolatility};
+use sui_mm::stats::MarketStats;
+use sui_mm::candles::{backfill_fills, write_csv, CandleBuilder};
+use sui_mm::subscriber::feed_stats;
 
 
-#[derive(Parser)]
-struct Cli {
+#[derive(Args)]
+struct RunArgs {
+    #[arg(short,long, default_value_t = 1)]
+    price: u64,
+    #[arg(short,long, default_value_t = 30)]
+    calculations: u64,
+    // maximum age of oracle price in seconds
+    #[arg(long, default_value_t = 30)]
+    max_price_age: i64,
+    // maximum oracle confidence interval relative to price
+    #[arg(long, default_value_t = 0.01)]
+    max_conf_ratio: f64,
+    // maximum relative difference between oracle price and deepbook mid
+    #[arg(long, default_value_t = 0.02)]
+    max_mid_deviation: f64,
+    // pyth feed id of base asset in USD
+    #[arg(long, default_value = SUI_PRICE_FEED)]
+    base_feed: String,
+    // pyth feed id of quote asset in USD
+    #[arg(long, default_value = USDC_PRICE_FEED)]
+    quote_feed: String,
+    // pyth feed id quoting base directly in quote, overrides base_feed and quote_feed
+    #[arg(long)]
+    direct_feed: Option<String>,
+    // price quotes are centered on: spot or ema
+    #[arg(long, default_value = "spot")]
+    anchor: PriceAnchor,
+    // spread added per unit of oracle conf/price
+    #[arg(long, default_value_t = 0.0)]
+    conf_multiplier: f64,
+    // fraction of ema/spot gap by which quotes are shifted
+    #[arg(long, default_value_t = 0.0)]
+    momentum_weight: f64,
+    // weight of pyth hermes price in median of price sources
+    #[arg(long, default_value_t = 1.0)]
+    pyth_weight: f64,
+    // weight of deepbook mid in median of price sources, 0 - used only as fallback with --price-fallback
+    #[arg(long, default_value_t = 0.0)]
+    mid_weight: f64,
+    // weight of deepbook vwap in median of price sources, 0 - used only as fallback with --price-fallback
+    #[arg(long, default_value_t = 0.0)]
+    vwap_weight: f64,
+    // minimum number of healthy weighted price sources, otherwise fallback is used
+    #[arg(long, default_value_t = 1)]
+    min_sources: usize,
+    // PriceInfoObject of base feed (or direct feed) on sui, enables on-chain pyth price source
+    #[arg(long)]
+    onchain_base_object: Option<String>,
+    // PriceInfoObject of quote feed on sui
+    #[arg(long)]
+    onchain_quote_object: Option<String>,
+    // weight of on-chain pyth price in median of price sources, 0 - used only as fallback with --price-fallback
+    #[arg(long, default_value_t = 1.0)]
+    onchain_weight: f64,
+    // with less than min_sources healthy weighted sources quote off the first healthy 0 weight source instead of stopping,
+    // the deepbook mid fallback is not checked against the book
+    #[arg(long, action = ArgAction::SetTrue)]
+    price_fallback: bool,
+    // number of gas coins split for transactions in flight
+    #[arg(long, default_value_t = 4)]
+    gas_coins: usize,
+    // balance of each gas coin in MIST
+    #[arg(long, default_value_t = 1_000_000_000)]
+    gas_coin_amount: u64,
+    #[command(flatten)]
+    risk: RiskArgs,
+    #[command(flatten)]
+    inventory: InventoryArgs,
+    #[command(flatten)]
+    breaker: BreakerArgs,
+    // directory of pnl and fills cursor files
+    #[arg(long, default_value = ".")]
+    state_dir: String,
+    // seconds between polls of own fills
+    #[arg(long, default_value_t = 5)]
+    fills_interval: u64,
+    // sui websocket url, fills for market stats are streamed from an event subscription instead of polled
+    #[arg(long)]
+    ws_url: Option<String>,
+}
+
+#[derive(Args)]
+struct BreakerArgs {
+    // realized loss in raw quote units over loss window which halts quoting, 0 disables
+    #[arg(long, default_value_t = 100_000_000.0)]
+    max_loss: f64,
+    #[arg(long, default_value_t = 3600)]
+    loss_window_sec: u64,
+    // time without healthy oracle price which halts quoting
+    #[arg(long, default_value_t = 60)]
+    max_oracle_stale_sec: u64,
+    // share of failed rpc calls among the last rpc_window calls which halts quoting
+    #[arg(long, default_value_t = 0.5)]
+    max_rpc_error_rate: f64,
+    #[arg(long, default_value_t = 20)]
+    rpc_window: usize,
+    // relative distance of deepbook mid from oracle price which halts quoting
+    #[arg(long, default_value_t = 0.05)]
+    max_mid_divergence: f64,
+    #[arg(long, default_value_t = 5)]
+    max_consecutive_failures: usize,
+    // quoting is paused this long after a breaker trips
+    #[arg(long, default_value_t = 60)]
+    breaker_cooldown_sec: u64,
+    // bot cancels all orders and exits when this file exists
+    #[arg(long)]
+    kill_file: Option<String>,
+    // local port of http kill switch, POST /kill
+    #[arg(long)]
+    kill_port: Option<u16>,
+}
+
+impl BreakerArgs {
+    fn config(&self) -> BreakerConfig {
+        return BreakerConfig{
+            max_loss: self.max_loss,
+            loss_window_ms: self.loss_window_sec * 1000,
+            max_oracle_stale_ms: self.max_oracle_stale_sec * 1000,
+            max_rpc_error_rate: self.max_rpc_error_rate,
+            rpc_window: self.rpc_window,
+            max_mid_divergence: self.max_mid_divergence,
+            max_consecutive_failures: self.max_consecutive_failures,
+            cooldown_ms: self.breaker_cooldown_sec * 1000,
+        }
+    }
+
+    // SIGINT/SIGTERM always trigger the kill switch
+    async fn kill_switch(&self) -> KillSwitch {
+        let kill_switch = KillSwitch::new(self.kill_file.clone());
+        kill_switch.listen_signals();
+        if self.kill_port.is_some() {
+            kill_switch.listen_http(self.kill_port.unwrap()).await;
+        }
+        return kill_switch;
+    }
+}
+
+#[derive(Args)]
+struct InventoryArgs {
+    // inventory limits are fraction of total value held in base instead of raw base units
+    #[arg(long, default_value_t = false)]
+    inventory_fraction: bool,
+    // below this only bid is quoted
+    #[arg(long)]
+    min_inventory: Option<f64>,
+    // above this only ask is quoted
+    #[arg(long)]
+    max_inventory: Option<f64>,
+    // below this inventory is bought back to min_inventory with IOC order
+    #[arg(long)]
+    hard_min_inventory: Option<f64>,
+    // above this inventory is sold down to max_inventory with IOC order
+    #[arg(long)]
+    hard_max_inventory: Option<f64>,
+    // IOC order price distance from oracle price
+    #[arg(long, default_value_t = 0.005)]
+    flatten_slippage: f64,
+}
+
+impl InventoryArgs {
+    fn config(&self) -> Option<InventoryConfig> {
+        if self.min_inventory.is_none() && self.max_inventory.is_none() && self.hard_min_inventory.is_none() && self.hard_max_inventory.is_none() {
+            return None;
+        }
+        return Some(InventoryConfig{
+            fraction: self.inventory_fraction,
+            min: self.min_inventory,
+            max: self.max_inventory,
+            hard_min: self.hard_min_inventory,
+            hard_max: self.hard_max_inventory,
+            flatten_slippage: self.flatten_slippage,
+        })
+    }
+}
+
+#[derive(Args)]
+struct RiskArgs {
+    // maximum notional of one order in raw quote units
+    #[arg(long, default_value_t = 1_000_000_000)]
+    max_order_notional: u64,
+    // maximum notional of open orders and new order on one side in raw quote units
+    #[arg(long, default_value_t = 2_000_000_000)]
+    max_open_notional: u64,
+    // maximum relative distance of order price from oracle price
+    #[arg(long, default_value_t = 0.05)]
+    max_oracle_deviation: f64,
+    // maximum relative distance of order price from deepbook mid
+    #[arg(long, default_value_t = 0.05)]
+    max_order_mid_deviation: f64,
+    #[arg(long, default_value_t = 60)]
+    max_orders_per_minute: usize,
+}
+
+impl RiskArgs {
+    fn config(&self) -> RiskConfig {
+        return RiskConfig{
+            max_order_notional: self.max_order_notional,
+            max_open_notional: self.max_open_notional,
+            max_oracle_deviation: self.max_oracle_deviation,
+            max_mid_deviation: self.max_order_mid_deviation,
+            max_orders_per_minute: self.max_orders_per_minute,
+        }
+    }
+}
+
+#[derive(Args)]
+struct RunMultiArgs {
+    // json file with list of pools to quote, see PoolConfig
+    #[arg(long, default_value = "pools_config.json")]
+    config: String,
     #[arg(short,long, default_value_t = 1)]
     price: u64,
     #[arg(short,long, default_value_t = 30)]
     calculations: u64,
+    #[arg(long, default_value_t = 30)]
+    max_price_age: i64,
+    #[arg(long, default_value_t = 0.01)]
+    max_conf_ratio: f64,
+    #[arg(long, default_value_t = 0.02)]
+    max_mid_deviation: f64,
+    // number of gas coins split for transactions in flight
+    #[arg(long, default_value_t = 4)]
+    gas_coins: usize,
+    // balance of each gas coin in MIST
+    #[arg(long, default_value_t = 1_000_000_000)]
+    gas_coin_amount: u64,
+    #[command(flatten)]
+    risk: RiskArgs,
+    #[command(flatten)]
+    breaker: BreakerArgs,
+    // directory of pnl and fills cursor files
+    #[arg(long, default_value = ".")]
+    state_dir: String,
+    // seconds between polls of own fills
+    #[arg(long, default_value_t = 5)]
+    fills_interval: u64,
+    // sui websocket url, fills for market stats are streamed from an event subscription instead of polled
+    #[arg(long)]
+    ws_url: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    // run market making bot
+    Run(RunArgs),
+    // run market making bot on several pools, orders of pools sharing an account are sent in one transaction
+    RunMulti(RunMultiArgs),
+    // list all deepbook pools with best bid/ask, spread and 24h volume
+    Pools {
+        // volume, spread or pair
+        #[arg(long, default_value = "volume")]
+        sort: PoolSort,
+    },
+    // custodian balance of account cap in the pool and wallet balances
+    Balance,
+    // open orders of account cap in the pool
+    Orders,
+    // cancel all open orders in the pool
+    CancelAll,
+    // cancel one order
+    Cancel {
+        order_id: u64,
+    },
+    // place limit order, price and quantity in deepbook units
+    Place {
+        #[arg(long)]
+        price: u64,
+        #[arg(long)]
+        quantity: u64,
+        #[arg(long, value_parser = ["bid", "ask"])]
+        side: String,
+        // none, ioc, fok or post
+        #[arg(long, default_value = "post", value_parser = ["none", "ioc", "fok", "post"])]
+        restriction: String,
+        // expiration in ms from now, if not provided 1 hour is used
+        #[arg(long)]
+        expire_ms: Option<u64>,
+    },
+    // deposit base or quote asset to custodian
+    Deposit {
+        #[arg(long)]
+        amount: u64,
+        #[arg(long, value_parser = ["base", "quote"])]
+        asset: String,
+        // coin to split the amount from, if not provided it is split from gas coin for SUI and from a coin of the asset with enough balance otherwise
+        #[arg(long)]
+        coin: Option<String>,
+    },
+    // withdraw base or quote asset from custodian to wallet
+    Withdraw {
+        #[arg(long)]
+        amount: u64,
+        #[arg(long, value_parser = ["base", "quote"])]
+        asset: String,
+    },
+    // create account cap and transfer it to wallet
+    CreateAccount {
+        // name of the account in accounts file
+        #[arg(long)]
+        name: Option<String>,
+    },
+    // create child cap of the account, it shares custodian balances with the account
+    CreateChildAccount {
+        #[arg(long)]
+        name: Option<String>,
+    },
+    // all account caps of wallet with names and balances in the pool
+    Accounts,
+    // top of the order book
+    Book,
+    // realized and unrealized pnl per day of account in the pool, from the pnl file of the bot if there is one,
+    // otherwise from own fills of the last days only, position held before them is not included
+    Pnl {
+        // days of fills to fetch or days of pnl file to print
+        #[arg(long, default_value_t = 1)]
+        days: u64,
+        // directory of pnl files of the bot
+        #[arg(long, default_value = ".")]
+        state_dir: String,
+        // pyth feed id of base asset in USD, position is marked at the feed price
+        #[arg(long, default_value = SUI_PRICE_FEED)]
+        base_feed: String,
+        // pyth feed id of quote asset in USD
+        #[arg(long, default_value = USDC_PRICE_FEED)]
+        quote_feed: String,
+        // pyth feed id quoting base directly in quote, overrides base_feed and quote_feed
+        #[arg(long)]
+        direct_feed: Option<String>,
+    },
+    // OHLCV candles from fills in the pool with Parkinson volatility of them
+    Candles {
+        // candle length in seconds
+        #[arg(long, default_value_t = 60)]
+        interval: u64,
+        // hours of fills to fetch
+        #[arg(long, default_value_t = 24)]
+        hours: u64,
+        // csv file to write candles to, printed if not set
+        #[arg(long)]
+        csv: Option<String>,
+    },
+}
+
+#[derive(Parser)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
     #[arg(short,long, default_value = "0.0.0")]
     version: String,
     #[arg(short, long, action)]
     debug: bool,
+    // deepbook pool id
+    #[arg(long, default_value = SUI_USDC_POOL)]
+    pool: String,
+    // asset pair i.e. SUI/USDC, overrides pool, pool is looked up in the pool registry and has to be the only one of the pair
+    #[arg(long)]
+    pair: Option<String>,
+    // file where pools from PoolCreated events are cached
+    #[arg(long, default_value = "pools.json")]
+    pools_cache: String,
+    // pools cache older than this is fetched again
+    #[arg(long, default_value_t = 24)]
+    pools_cache_hours: u64,
+    // account cap name or id, the first account cap of wallet if not provided
+    #[arg(long)]
+    account: Option<String>,
+    // file with names of account caps
+    #[arg(long, default_value = "accounts.json")]
+    accounts: String,
+    #[command(flatten)]
+    signer: SignerArgs,
+    // retries of transaction submission on rpc errors and timeouts
+    #[arg(long, default_value_t = 3)]
+    retries: u32,
+    // backoff before first retry in ms, doubled after each retry
+    #[arg(long, default_value_t = 200)]
+    retry_backoff_ms: u64,
+    // timeout of one submission in ms
+    #[arg(long, default_value_t = 10_000)]
+    submit_timeout_ms: u64,
+    // dry run gas cost is multiplied by margin to get gas budget
+    #[arg(long, default_value_t = 1.2)]
+    gas_margin: f64,
+    // cap of estimated gas budget in MIST
+    #[arg(long, default_value_t = 50_000_000)]
+    max_gas_budget: u64,
+    // reference gas price multiplier of cancels
+    #[arg(long, default_value_t = 1.0)]
+    gas_tip: f64,
+}
+
+#[derive(Args)]
+struct SignerArgs {
+    // mnemonic (from $SUI_WALLET), keystore, key or remote
+    #[arg(long, default_value = "mnemonic", value_parser = ["mnemonic", "keystore", "key", "remote"])]
+    signer: String,
+    // scheme of mnemonic key: ed25519, secp256k1 or secp256r1
+    #[arg(long, default_value = "ed25519")]
+    key_scheme: String,
+    // derivation path of mnemonic key, default path of the scheme if not provided
+    #[arg(long)]
+    derivation_path: Option<String>,
+    // sui keystore file i.e. ~/.sui/sui_config/sui.keystore
+    #[arg(long)]
+    keystore: Option<String>,
+    // file with base64 private key in sui.keystore format
+    #[arg(long)]
+    key_file: Option<String>,
+    // url of remote signer i.e. http://127.0.0.1:9000/sign or unix:///run/signer.sock
+    #[arg(long)]
+    remote_signer: Option<String>,
+    // time to wait for the remote signer in milliseconds
+    #[arg(long, default_value_t = 2_000)]
+    remote_signer_timeout_ms: u64,
+    // address to sign with, for keystore and remote signer
+    #[arg(long)]
+    address: Option<String>,
+}
+
+fn build_signer(args: &SignerArgs) -> Box<dyn Signer> {
+    let address = args.address.clone().map(|x| SuiAddress::from_str(&x).expect("invalid address"));
+    match args.signer.as_str() {
+        "keystore" => Box::new(KeystoreSigner::from_keystore_file(&args.keystore.clone().expect("--keystore is not set"), address)),
+        "key" => Box::new(PrivateKeySigner::from_file(&args.key_file.clone().expect("--key-file is not set"))),
+        "remote" => Box::new(RemoteSigner::new(args.remote_signer.clone().expect("--remote-signer is not set"), address.expect("--address is not set"),
+                                                args.remote_signer_timeout_ms)),
+        _ => {
+            let mnemonic = env::var("SUI_WALLET").expect("$SUI_WALLET is not set");
+            let scheme = parse_scheme(&args.key_scheme).expect("unknown key scheme");
+            Box::new(KeystoreSigner::from_mnemonic(&mnemonic, scheme, args.derivation_path.clone()))
+        }
+    }
+}
+
+// prints created account cap and saves it under name
+fn save_created_account(response: Option<SuiTransactionBlockResponse>, name: Option<String>, names: &mut AccountNames, path: &str) {
+    let created = response.as_ref()
+        .and_then(|x| x.effects.as_ref())
+        .and_then(|x| x.created().first().map(|o| o.reference.object_id));
+    if created.is_none() {
+        println!("account cap not created: {:?}", response.map(|x| x.digest));
+        return;
+    }
+    println!("account cap: {}", created.unwrap());
+    if name.is_some() {
+        names.insert(name.unwrap(), created.unwrap());
+        names.save(path);
+    }
 }
 
+// default gas budget of bot transactions, coins below it are merged
+const GAS_BUDGET: u64 = 50_000_000;
+const GAS_DUST: u64 = 50_000_000;
+
+async fn list_pools(client: &SuiClient, pools_cache: &str, pools_cache_ttl_s: u64, sort: PoolSort) {
+    let registry = PoolRegistry::load_or_fetch(client, pools_cache, pools_cache_ttl_s).await;
+    let mut summaries = vec![];
+    for record in registry.pools.iter() {
+        summaries.push(summarize_pool(client, record).await);
+    }
+    sort_summaries(&mut summaries, sort);
+    println!("{:<16} {:<68} {:>14} {:>14} {:>10} {:>14} {:>8}", "pair", "pool", "bid", "ask", "spread bps", "volume 24h", "trades");
+    for item in summaries.iter() {
+        let spread = if item.spread_bps.is_some() {format!("{:.2}", item.spread_bps.unwrap())} else {"-".to_string()};
+        println!("{:<16} {:<68} {:>14.6} {:>14.6} {:>10} {:>14} {:>8}", item.pair, item.pool_id.to_string(), item.best_bid, item.best_ask, spread, item.volume_24h, item.trades_24h);
+    }
+}
 
 #[tokio::main]
 async fn main() {
@@ -52,132 +521,415 @@ async fn main() {
     } else {
         env_logger::builder().filter_level(LevelFilter::Info).init();
     }
-    let price_interval_sec = cli.price.clone();
-    let calculations_interval_sec = cli.calculations.clone();
     let sui_rpc = env::var("SUI_RPC").expect("$SUI_RPC is not set");
     let client = SuiClientBuilder::default()
         .build(sui_rpc.clone())
         .await.unwrap();
-    let pool_id = ObjectID::from_str("0x4405b50d791fd3346754e8171aaab6bc2ed26c2c46efdd033c14b30ae507ac33").unwrap();
-    let transaction_wrapper = TransactionWrapper::new(&client);
-    let response = get_account_cap(&client, &transaction_wrapper.signer ).await.unwrap();
-    let account_cap_id = response.data[0].data.clone().unwrap().object_id;
+    let mut pool_id = ObjectID::from_str(&cli.pool).unwrap();
+    let pools_cache_ttl_s = cli.pools_cache_hours * 3600;
+    if cli.pair.is_some() {
+        let pair = cli.pair.clone().unwrap();
+        let mut registry = PoolRegistry::load_or_fetch(&client, &cli.pools_cache, pools_cache_ttl_s).await;
+        // pool may have been created after the cache was written
+        if registry.find_all_by_pair(&pair).len() == 0 {
+            registry = PoolRegistry::refresh(&client, &cli.pools_cache).await;
+        }
+        let record = registry.resolve_pair(&pair).unwrap_or_else(|e| panic!("{}", e));
+        info!("pool for {}: {:?}", pair, record);
+        pool_id = record.pool_id;
+    }
+    match cli.command {
+        Commands::Pools { sort } => {
+            list_pools(&client, &cli.pools_cache, pools_cache_ttl_s, sort).await;
+            return;
+        }
+        _ => {}
+    }
+    let mut transaction_wrapper = TransactionWrapper::with_signer(&client, build_signer(&cli.signer));
+    transaction_wrapper.submit_config = SubmitConfig{ retries: cli.retries, backoff_ms: cli.retry_backoff_ms, timeout_ms: cli.submit_timeout_ms };
+    transaction_wrapper.gas_config = GasConfig{ budget_margin: cli.gas_margin, max_budget: cli.max_gas_budget, tip_multiplier: cli.gas_tip, ..GasConfig::default() };
+    let mut names = AccountNames::load(&cli.accounts);
+    let owned_caps = get_all_account_caps(&client, &transaction_wrapper.signer).await;
+    match cli.command {
+        Commands::CreateAccount { ref name } => {
+            let response = transaction_wrapper.execute_ptx(create_account(transaction_wrapper.signer), None, None, None).await;
+            save_created_account(response, name.clone(), &mut names, &cli.accounts);
+            return;
+        }
+        _ => {}
+    }
+    let account_cap_id = names.resolve(cli.account.as_deref(), &owned_caps).expect("no account cap, create one with create-account");
+    match cli.command {
+        Commands::Run(args) => {
+            run(&client, &transaction_wrapper, sui_rpc, pool_id, account_cap_id, args).await;
+            return;
+        }
+        Commands::RunMulti(args) => {
+            run_multi(&client, &transaction_wrapper, sui_rpc, &names, &owned_caps, account_cap_id, args).await;
+            return;
+        }
+        Commands::CreateChildAccount { ref name } => {
+            let admin = transaction_wrapper.object_ref(account_cap_id).await;
+            let response = transaction_wrapper.execute_ptx(create_child_account(admin, transaction_wrapper.signer), None, None, None).await;
+            save_created_account(response, name.clone(), &mut names, &cli.accounts);
+            return;
+        }
+        Commands::Accounts => {
+            for cap_id in owned_caps.iter() {
+                let order_wrapper = OrderWrapper::new(&client, pool_id, Some(*cap_id), None).await;
+                let (balance_data, _, open_orders) = order_wrapper.get_data().await;
+                let name = names.name_of(cap_id).cloned().unwrap_or("-".to_string());
+                println!("{} {} base: {}/{} quote: {}/{} open orders: {}", name, cap_id, balance_data[0], balance_data[1], balance_data[2], balance_data[3], open_orders.len());
+            }
+            return;
+        }
+        _ => {}
+    }
+    let order_wrapper = OrderWrapper::new(&client, pool_id, Some(account_cap_id), None).await;
+    match cli.command {
+        Commands::Balance => {
+            let (balance_data, _, _) = order_wrapper.get_data().await;
+            println!("account cap: {}", account_cap_id);
+            println!("{} available: {} locked: {}", order_wrapper.base_asset, balance_data[0], balance_data[1]);
+            println!("{} available: {} locked: {}", order_wrapper.quote_asset, balance_data[2], balance_data[3]);
+            let balances = client.coin_read_api().get_all_balances(transaction_wrapper.signer).await.unwrap();
+            for balance in balances.iter() {
+                println!("wallet {}: {}", balance.coin_type, balance.total_balance);
+            }
+        }
+        Commands::Orders => {
+            let (_, _, open_orders) = order_wrapper.get_data().await;
+            for order in open_orders.iter() {
+                println!("{} {} price: {} quantity: {}/{} expire: {}", order.order_id, if order.is_bid {"bid"} else {"ask"}, order.price, order.quantity, order.original_quantity, order.expire_timestamp);
+            }
+        }
+        Commands::CancelAll => {
+            let account_cap_ref = order_wrapper.fetch_account_cap_object_ref().await;
+            let tb = order_wrapper.clone().cancel_all_orders(ProgrammableTransactionBuilder::new(), account_cap_ref);
+            println!("{:?}", transaction_wrapper.process_ptx(tb.finish(), None, None, None).await);
+        }
+        Commands::Cancel { order_id } => {
+            let account_cap_ref = order_wrapper.fetch_account_cap_object_ref().await;
+            let tb = order_wrapper.cancel_order(ProgrammableTransactionBuilder::new(), order_id, account_cap_ref);
+            println!("{:?}", transaction_wrapper.process_ptx(tb.finish(), None, None, None).await);
+        }
+        Commands::Place { price, quantity, side, restriction, expire_ms } => {
+            let restriction = match restriction.as_str() {
+                "none" => LIMIT_ORDER_NO_RESTRICTION,
+                "ioc" => LIMIT_ORDER_IMMEDIATE_OR_CANCEL,
+                "fok" => LIMIT_ORDER_FILL_OR_KILL,
+                _ => LIMIT_ORDER_POST_OR_ABORT,
+            };
+            let t = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+            let account_cap_ref = order_wrapper.fetch_account_cap_object_ref().await;
+            let tb = order_wrapper.place_limit_order(ProgrammableTransactionBuilder::new(),
+                                                     price,
+                                                     quantity,
+                                                     side == "bid",
+                                                     restriction,
+                                                     None,
+                                                     account_cap_ref,
+                                                     expire_ms.map(|x| t + x),
+            );
+            println!("{:?}", transaction_wrapper.process_ptx(tb.finish(), None, None, None).await);
+        }
+        Commands::Deposit { amount, asset, coin } => {
+            let coin_type = if asset == "base" {order_wrapper.base_asset.clone()} else {order_wrapper.quote_asset.clone()};
+            let coin_ref = if coin.is_some() {
+                let coin_id = ObjectID::from_str(&coin.unwrap()).unwrap();
+                Some(client.read_api().get_object_with_options(coin_id, SuiObjectDataOptions::new()).await.unwrap().object().unwrap().object_ref())
+            } else if normalize_type(&coin_type) == normalize_type(SUI_TYPE) {
+                None
+            } else {
+                // gas coin is SUI, deposit of any other asset needs a coin of its type
+                let selected = select_coin(&client, transaction_wrapper.signer, &coin_type, amount).await;
+                if selected.is_none() {
+                    println!("no coin of {} with balance {}, merge coins or pass --coin", coin_type, amount);
+                    return;
+                }
+                selected
+            };
+            let account_cap_ref = order_wrapper.fetch_account_cap_object_ref().await;
+            let tb = order_wrapper.deposit(ProgrammableTransactionBuilder::new(), coin_ref, amount, asset == "base", account_cap_ref);
+            println!("{:?}", transaction_wrapper.process_ptx(tb.finish(), None, None, None).await);
+        }
+        Commands::Withdraw { amount, asset } => {
+            let account_cap_ref = order_wrapper.fetch_account_cap_object_ref().await;
+            let tb = order_wrapper.withdraw(ProgrammableTransactionBuilder::new(), amount, asset == "base", account_cap_ref, transaction_wrapper.signer);
+            println!("{:?}", transaction_wrapper.process_ptx(tb.finish(), None, None, None).await);
+        }
+        Commands::Book => {
+            let (bids, asks) = order_wrapper.get_bid_ask().await;
+            println!("asks:");
+            for order in asks.orders.iter().rev() {
+                println!("{:>14} {:>18}", order.price, order.quantity);
+            }
+            println!("bids:");
+            for order in bids.orders.iter() {
+                println!("{:>14} {:>18}", order.price, order.quantity);
+            }
+        }
+        Commands::Pnl { days, state_dir, base_feed, quote_feed, direct_feed } => {
+            let t = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+            let since = t.saturating_sub(days * DAY_MS);
+            let path = pnl_path(&state_dir, pool_id);
+            let mut engine = if Path::new(&path).exists() {
+                println!("pnl since the first run of the bot as of its last update, from {}", path);
+                PnlEngine::load(&path)
+            } else {
+                println!("no pnl file {}, pnl of fills in the last {} days only, position held before them is not included", path, days);
+                let mut events = query_fills_since(&client, pool_id, order_wrapper.base_asset.clone(), order_wrapper.quote_asset.clone(), since).await;
+                events.reverse();
+                let owner = get_account_cap_owner(&client, account_cap_id).await.expect("could not read owner of account cap");
+                let owners = vec![owner];
+                let mut engine = PnlEngine::default();
+                for event in events.iter() {
+                    let fill = own_fill(event, pool_id, &owners);
+                    if fill.is_none() {
+                        continue;
+                    }
+                    let fill = fill.unwrap();
+                    for own in own_fills(&fill.event, fill.timestamp_ms, &owners).iter() {
+                        engine.apply(own);
+                    }
+                }
+                engine
+            };
+            let (_, _, price_decimals) = pool_decimals(&client, &order_wrapper.base_asset, &order_wrapper.quote_asset).await;
+            let pair = if direct_feed.is_some() {FeedPair::Direct(direct_feed.unwrap())} else {FeedPair::ThroughUsd { base: base_feed, quote: quote_feed }};
+            let price = PriceOracle::new(pair, OracleConfig::default()).get_price().await;
+            let total = match price {
+                Ok(x) => engine.mark(t, x.price * price_decimals as f64),
+                Err(err) => {
+                    println!("no price to mark position at: {:?}", err);
+                    engine.net()
+                }
+            };
+            let first_day = day_of(since);
+            for (day, pnl) in engine.days.iter().filter(|x| *x.0 >= first_day) {
+                println!("{} fills: {} volume: {:.0} realized: {:.0} rebates: {:.0} commissions: {:.0} net: {:.0} position: {:.0} unrealized: {:.0}",
+                         day, pnl.fills, pnl.volume, pnl.realized, pnl.rebates, pnl.commissions, pnl.net(), pnl.position, pnl.unrealized);
+            }
+            println!("position: {:.0} average cost: {:.0} net: {:.0} total: {:.0}", engine.position, engine.average_cost, engine.net(), total);
+        }
+        Commands::Candles { interval, hours, csv } => {
+            let t = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+            let mut builder = CandleBuilder::new(interval * 1000, (hours * 3600 / interval.max(1)) as usize + 1);
+            let (base_decimals, _, price_decimals) = pool_decimals(&client, &order_wrapper.base_asset, &order_wrapper.quote_asset).await;
+            let fills = backfill_fills(&client, &mut builder, pool_id, order_wrapper.base_asset.clone(), order_wrapper.quote_asset.clone(),
+                                       t.saturating_sub(hours * 3_600_000), price_decimals, base_decimals).await;
+            let candles = builder.candles();
+            if csv.is_some() {
+                write_csv(&csv.clone().unwrap(), &candles);
+                println!("{} candles from {} fills written to {}", candles.len(), fills, csv.unwrap());
+            } else {
+                for candle in candles.iter() {
+                    println!("{} open: {:.6} high: {:.6} low: {:.6} close: {:.6} volume: {:.3} notional: {:.3} fills: {}",
+                             candle.start_ms, candle.open, candle.high, candle.low, candle.close, candle.volume, candle.notional, candle.count);
+                }
+            }
+            let mut parkinson = ParkinsonVolatility::new(candles.len());
+            for candle in candles.iter() {
+                parkinson.update(candle.bar());
+            }
+            println!("annualized parkinson volatility: {:?}", parkinson.annualized());
+        }
+        _ => {}
+    }
+}
+
+async fn run(client: &SuiClient, transaction_wrapper: &TransactionWrapper<'_>, sui_rpc: String, pool_id: ObjectID, account_cap_id: ObjectID, args: RunArgs) {
+    let price_interval_sec = args.price.clone();
+    let calculations_interval_sec = args.calculations.clone();
+    let oracle_config = OracleConfig{
+        max_age_s: args.max_price_age,
+        max_conf_ratio: args.max_conf_ratio,
+        max_mid_deviation: args.max_mid_deviation,
+    };
     let order_wrapper = OrderWrapper::new(&client, pool_id, Some(account_cap_id), None).await;
+    // pool may be any pair given by --pair or --pool
+    let (base_decimals, quote_decimals, price_decimals) = pool_decimals(&client, &order_wrapper.base_asset, &order_wrapper.quote_asset).await;
+    let pool_config = PoolConfig{
+        pool: pool_id.to_string(),
+        base_feed: Some(args.base_feed.clone()),
+        quote_feed: Some(args.quote_feed.clone()),
+        direct_feed: args.direct_feed.clone(),
+        price_decimals,
+        base_decimals,
+        quote_decimals,
+        anchor: args.anchor,
+        conf_multiplier: args.conf_multiplier,
+        momentum_weight: args.momentum_weight,
+        account: None,
+        inventory: args.inventory.config(),
+        pyth_weight: args.pyth_weight,
+        mid_weight: args.mid_weight,
+        vwap_weight: args.vwap_weight,
+        min_sources: args.min_sources,
+        onchain_base_object: args.onchain_base_object.clone(),
+        onchain_quote_object: args.onchain_quote_object.clone(),
+        onchain_weight: args.onchain_weight,
+        price_fallback: args.price_fallback,
+    };
+    let (tick_size, lot_size) = get_pool_params(&client, pool_id).await
+        .unwrap_or_else(|| panic!("could not read tick and lot size of pool {}", pool_id));
+    let mut risk = RiskChecker::new(args.risk.config(), tick_size, lot_size, price_decimals);
+    let gas_pool = GasPool::new(&client, transaction_wrapper.signer, GAS_DUST);
+    gas_pool.prepare(transaction_wrapper, args.gas_coins, args.gas_coin_amount, GAS_BUDGET).await;
     let (base_asset, quote_asset) = (order_wrapper.base_asset.clone(), order_wrapper.quote_asset.clone());
-    let pyth_feeder = PythFeeder::new_suiusdc();
-    let result = pyth_feeder.get_latest_price().await.unwrap();
-    let price = get_sui_usdc_price(result);
+    let price_oracle = PriceOracle::new(pool_config.feed_pair(), oracle_config);
+    let price_pool_config = pool_config.clone();
+    let price_rpc = sui_rpc.clone();
+    // price and whether it is the deepbook mid itself
+    let price: Option<(ValidatedPrice, bool)> = None;
     let sui_usdc_price = Arc::new(Mutex::new(price));
     let sui_usdc_price_clone = Arc::clone(&sui_usdc_price);
     let volatility_cal: Option<f64> = None;
     let vol_mutex = Arc::new(Mutex::new(volatility_cal));
     let vol_mutex_clone = Arc::clone(&vol_mutex);
-    let calculations = get_fills(&client, base_asset.to_string(), quote_asset.to_string()).await;
-    let calc_mutex = Arc::new(Mutex::new(calculations));
-    let calc_mutex_clone = Arc::clone(&calc_mutex);
+    let stats = Arc::new(Mutex::new(MarketStats::with_defaults(pool_id)));
+    let feed_stats_of = Arc::clone(&stats);
+    let ws_url = args.ws_url.clone();
     tokio::spawn(
         async move {
             let client = SuiClientBuilder::default()
                 .build(sui_rpc.clone())
                 .await.unwrap();
-            let ba = base_asset.to_string();
-            let qa = quote_asset.to_string();
-            loop {
-                sleep(Duration::from_secs(calculations_interval_sec)).await;
-                let calc = get_fills(&client, ba.clone(), qa.clone()).await;
-                info!("calculation on events for pool: {:?}", calc);
-                let mut guard = calc_mutex_clone.lock().await;
-                *guard = calc;
-            }
-
+            feed_stats(client, ws_url, base_asset.to_string(), quote_asset.to_string(), feed_stats_of, calculations_interval_sec).await;
         }
     );
     tokio::spawn(
         async move {
-            let mut volatility = Volatility{ prices: vec![], length: 300 };
+            let client = SuiClientBuilder::default()
+                .build(price_rpc)
+                .await.unwrap();
+            let aggregator = pool_aggregator(&client, &price_pool_config, Box::new(price_oracle), oracle_config).await;
+            let mut volatility = QuoteVolatility::new(price_interval_sec);
             loop {
                 sleep(Duration::from_secs(price_interval_sec)).await;
-                let result_raw = pyth_feeder.get_latest_price().await;
-                if result_raw.is_none() {
+                let validated = aggregator.get_price_with_source().await;
+                let mut price_guard = sui_usdc_price_clone.lock().await;
+                if validated.is_err() {
+                    warn!("oracle unhealthy: {:?}", validated);
+                    *price_guard = None;
                     continue;
                 }
-                let price = get_sui_usdc_price(result_raw.unwrap());
-                let mut price_guard = sui_usdc_price_clone.lock().await;
-                volatility.insert(price);
-                if volatility.prices.len() == volatility.length {
-                    let result = volatility.clone().volatility();
-                        let vol_calc = result.unwrap();
-                        let mut vol_guard = vol_mutex_clone.lock().await;
-                        *vol_guard = Some(vol_calc);
+                let (price, book_mid) = validated.unwrap();
+                if book_mid {
+                    warn!("price is the deepbook mid, quoting without mid deviation check");
+                }
+                volatility.update(&price);
+                let result = volatility.volatility();
+                if result.is_some() {
+                    let mut vol_guard = vol_mutex_clone.lock().await;
+                    *vol_guard = result;
                 }
-                *price_guard = price;
+                *price_guard = Some((price, book_mid));
             }
         }
     );
+    let kill_switch = args.breaker.kill_switch().await;
+    let mut breaker = CircuitBreaker::new(args.breaker.config(), SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64);
+    let order_wrappers = vec![order_wrapper.clone()];
+    let account_caps = vec![account_cap_id];
+    let mut pool_pnl = PoolPnl::new(&client, &order_wrapper, account_cap_id, &args.state_dir).await;
+    let mut last_fills_ms = 0;
     loop {
         sleep(Duration::from_millis(400)).await;
-        let price: f64 = sui_usdc_price.lock().await.clone();
-        let mut vol = vol_mutex.lock().await.clone();
-        let calc = calc_mutex.lock().await.clone();
-        if vol.is_some() {
-            let (balance_data, bid_ask_data, open_orders) = order_wrapper.get_data().await;
-            // TODO implement logic when there are already opened orders ...
-            // If size of existing order is less ...
-            // todo if open order is
+        // no transaction is in flight between ticks
+        gas_pool.maintain(transaction_wrapper).await;
+        let t = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+        if kill_switch.is_killed() {
+            let result = cancel_all_pools(transaction_wrapper, &gas_pool, &order_wrappers, &account_caps).await;
+            info!("cancelled all orders before exit");
+            log_submit_result(&result);
+            return;
+        }
+        if t.saturating_sub(last_fills_ms) >= args.fills_interval * 1000 {
+            pool_pnl.update().await;
+            last_fills_ms = t;
+        }
+        if breaker.paused(t) {
+            continue;
+        }
+        let anchored = sui_usdc_price.lock().await.clone();
+        let oracle_price = anchored.map(|x| x.0);
+        let tick_oracle_config = if anchored.is_some() && anchored.unwrap().1 {oracle_config.without_mid_check()} else {oracle_config};
+        let vol = vol_mutex.lock().await.clone();
+        breaker.record_oracle(t, oracle_price.is_some());
+        let data = order_wrapper.try_get_data().await;
+        breaker.record_rpc(data.is_some());
+        if data.is_none() {
+            if breaker.check(t, None, 0, 0, price_decimals).is_some() {
+                log_submit_result(&cancel_all_pools(transaction_wrapper, &gas_pool, &order_wrappers, &account_caps).await);
+            }
+            continue;
+        }
+        let (balance_data, bid_ask_data, open_orders) = data.unwrap();
+        if oracle_price.is_some() {
+            pool_pnl.engine.mark(t, oracle_price.unwrap().price * price_decimals as f64);
+        }
+        // realized only, marking inventory would trip on price moves alone
+        breaker.record_pnl(t, pool_pnl.engine.net());
+        if breaker.check(t, oracle_price.map(|x| x.price), bid_ask_data[0], bid_ask_data[1], price_decimals).is_some() {
             if open_orders.len() > 0 {
-                let order = open_orders.first().unwrap();
-                let diff = (((price * 1_000_000.0) - order.price as f64).abs()/order.price as f64).min((order.price as f64 - (((bid_ask_data[0] + bid_ask_data[1])/2) as f64)).abs()/order.price as f64);
-                let t = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
-                // if the price difference is greater than 1 percent
-                if diff > 0.01 || (order.expire_timestamp - t) < 60 * 1000 {
-                    // TODO cancel
-                    let account_cap_ref = order_wrapper.clone().fetch_account_cap_object_ref().await;
-                    let mut tb = ProgrammableTransactionBuilder::new();
-                    let tb = order_wrapper.clone().cancel_all_orders(tb, account_cap_ref);
-                    let result = transaction_wrapper.process_ptx(tb.finish(), None, None, None).await;
-                    info!("cancel {:?} {} {}", result, diff,  (order.expire_timestamp - t) < 60 * 1000);
-                } else {
+                log_submit_result(&cancel_all_pools(transaction_wrapper, &gas_pool, &order_wrappers, &account_caps).await);
+            }
+            continue;
+        }
+        if vol.is_none() {
+            continue;
+        }
+        let snapshot = pool_snapshot(&stats, &bid_ask_data, t).await;
+        let action = decide(&balance_data, &bid_ask_data, &open_orders, oracle_price, vol, &snapshot, &pool_config, &tick_oracle_config, t);
+        let inventory = fetch_inventory(&client, &order_wrapper, transaction_wrapper.signer, &balance_data, &pool_config).await;
+        let action = apply_inventory(action, &inventory, &balance_data, &bid_ask_data, oracle_price, &pool_config, &tick_oracle_config, tick_size, lot_size);
+        let action = match action {
+            QuoteAction::Place(quotes) => {
+                if risk.check_quotes(&quotes, &open_orders, oracle_price.map(|x| x.price), &bid_ask_data, t).is_err() {
                     continue;
                 }
-                debug!("Orders opened: {:?}", open_orders);
-                continue;
-            };
-            let vol_number = vol.unwrap();
-            // TODO - check if prices bid and ask and pyth price is are too different ...
-            let orders_to_do = calculate_totals(&balance_data, price, None, None, calc, vol_number);
-            info!("Price: {} {:?} {:?} {:?}", price, vol, calc, bid_ask_data );
-            let t = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis();
-            if orders_to_do.is_some() {
-                let orders_calc = orders_to_do.unwrap();
-                let mut tb = ProgrammableTransactionBuilder::new();
-                let account_cap_ref = order_wrapper.fetch_account_cap_object_ref().await;
-                let t = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
-                if orders_calc.bid_quantity > 0 {
-                    tb = order_wrapper.place_limit_order(tb,
-                            orders_calc.bid_price,
-                        orders_calc.bid_quantity,
-                        true,
-                        LIMIT_ORDER_POST_OR_ABORT,
-                        None,
-                        account_cap_ref,
-                        Some(t + orders_calc.duration_ms),
-                    );
-                }
-                if orders_calc.ask_quantity > 0 {
-                    tb = order_wrapper.place_limit_order(tb,
-                                                    orders_calc.ask_price,
-                                                    orders_calc.ask_quantity,
-                                                    false,
-                                                    LIMIT_ORDER_POST_OR_ABORT,
-                                                    None,
-                                                    account_cap_ref,
-                                                    Some(t + orders_calc.duration_ms),
-                    );
+                QuoteAction::Place(quotes)
+            }
+            QuoteAction::Flatten(order) => {
+                let result = risk.check(&order, &vec![], &vec![], oracle_price.map(|x| x.price), bid_ask_data[0], bid_ask_data[1], t);
+                if result.is_err() {
+                    warn!("risk check rejected flatten {:?}: {:?}", order, result);
+                    continue;
                 }
-                let result = transaction_wrapper.process_ptx(tb.finish(), None, None, None).await;
-                info!("{:?}", result);
+                risk.record(1, t);
+                QuoteAction::Flatten(order)
             }
+            other => other,
+        };
+        let result = submit_action(transaction_wrapper, &gas_pool, &order_wrapper, account_cap_id, &action, t).await;
+        if result.is_none() {
+            continue;
         }
+        let result = result.unwrap();
+        breaker.record_submit(&result);
+        log_submit_result(&result);
     }
 }
 
+// pools without account in config use default_account_cap
+async fn run_multi(client: &SuiClient, transaction_wrapper: &TransactionWrapper<'_>, sui_rpc: String,
+                   names: &AccountNames, owned_caps: &Vec<ObjectID>, default_account_cap: ObjectID, args: RunMultiArgs) {
+    let oracle_config = OracleConfig{
+        max_age_s: args.max_price_age,
+        max_conf_ratio: args.max_conf_ratio,
+        max_mid_deviation: args.max_mid_deviation,
+    };
+    let pools = load_pool_configs(&args.config);
+    info!("running {} pools: {:?}", pools.len(), pools);
+    let account_caps: Vec<ObjectID> = pools.iter().map(|x| if x.account.is_some() {
+        names.resolve(x.account.as_deref(), owned_caps).expect("unknown account in pools config")
+    } else {
+        default_account_cap
+    }).collect();
+    let gas_pool = GasPool::new(&client, transaction_wrapper.signer, GAS_DUST);
+    gas_pool.prepare(transaction_wrapper, args.gas_coins, args.gas_coin_amount, GAS_BUDGET).await;
+    let kill_switch = args.breaker.kill_switch().await;
+    run_pools(client, sui_rpc, transaction_wrapper, &gas_pool, account_caps, pools, oracle_config, args.risk.config(),
+              args.breaker.config(), kill_switch, args.state_dir, args.ws_url, args.price, args.calculations, args.fills_interval).await;
+}
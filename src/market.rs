@@ -1,15 +1,12 @@
-use std::ops::Sub;
-use std::str::FromStr;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use log::{debug, info};
-use serde_derive::{Deserialize, Serialize};
+use log::warn;
 use sui_sdk::rpc_types::{EventFilter, SuiEvent};
 use sui_sdk::SuiClient;
 use sui_types::base_types::ObjectID;
 use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use sui_types::transaction::{CallArg, ObjectArg};
 use sui_types::TypeTag;
-use crate::constant::{DEEPBOOK_PKG, SUI_DECIMALS, SUI_USDC_DECIMALS};
+use crate::constant::DEEPBOOK_PKG;
+use crate::events::{decode_event, DeepBookEvent, OrderFilledEvent};
 
 /// returns (best_bid_price, best_ask_price)
 pub fn get_market_price(mut tb: ProgrammableTransactionBuilder,
@@ -35,49 +32,87 @@ pub fn get_market_price(mut tb: ProgrammableTransactionBuilder,
 { id: EventID { tx_digest: TransactionDigest(GrBu1rVaoyW6HLZAVHtx3SyZ1CiC34T7FCz38y2U4Tir), event_seq: 0 }, package_id: 0xa2ce75c54b8ee30b15b235faf8c6c01407bf90cf3fbcec5d84b04ec25400131a, transaction_module: Identifier("jk"), sender: 0x7fac4740148563dbebb980f4161ef7e7f7fdc0f7b6311227fafc7ef60899f096, type_: StructTag { address: 000000000000000000000000000000000000000000000000000000000000dee9, module: Identifier("clob_v2"), name: Identifier("OrderFilled"), type_params: [Struct(StructTag { address: 0000000000000000000000000000000000000000000000000000000000000002, module: Identifier("sui"), name: Identifier("SUI"), type_params: [] }), Struct(StructTag { address: 5d4b302506645c37ff133b98c4b50a5ae14841659738d6d733d59d0d217a93bf, module: Identifier("coin"), name: Identifier("COIN"), type_params: [] })] }, parsed_json: Object {"base_asset_quantity_filled": String("700000000000"), "base_asset_quantity_remaining": String("2700000000000"), "is_bid": Bool(true), "maker_address": String("0xf995d6df20e18421928ff0648bd583ccdf384ab05791d8be21d32977a37dacfc"), "maker_client_order_id": String("1708380292894207686"), "maker_rebates": String("249718"), "order_id": String("5925000"), "original_quantity": String("5000000000000"), "pool_id": String("0x4405b50d791fd3346754e8171aaab6bc2ed26c2c46efdd033c14b30ae507ac33"), "price": String("1783700"), "taker_address": String("0x11f8f568d871ff0cf829aca81e51a06a6869d12abe0b3351b914a4673ea3d857"), "taker_client_order_id": String("4399"), "taker_commission": String("249718")}
  */
 
-pub async fn get_fills(client: &SuiClient, base_asset: String, quote_asset: String) -> CalculationResult {
+// last 100 OrderFilled events of the pool, most recent first
+pub async fn query_fills(client: &SuiClient, base_asset: String, quote_asset: String) -> Vec<SuiEvent> {
+    return try_query_fills(client, base_asset, quote_asset).await.expect("could not query fills");
+}
+
+// same as query_fills, None if the rpc call fails
+pub async fn try_query_fills(client: &SuiClient, base_asset: String, quote_asset: String) -> Option<Vec<SuiEvent>> {
     let query = format!("0xdee9::clob_v2::OrderFilled<{base_asset}, {quote_asset}>");
     // let query = "0xdee9::clob_v2::OrderFilled<0x2::sui::SUI, 0x5d4b302506645c37ff133b98c4b50a5ae14841659738d6d733d59d0d217a93bf::coin::COIN>";
     let events_response = client.event_api().query_events(EventFilter::MoveEventType(query.parse().unwrap()),
                                                           None, Some(100),
                                                           true).await;
-    let unwrapped = events_response.unwrap().data;
-    // debug!("{:?}", unwrapped);
-    return calculate(&unwrapped);
+    if events_response.is_err() {
+        warn!("could not query fills: {:?}", events_response.err());
+        return None;
+    }
+    return Some(events_response.unwrap().data);
+}
+
+// OrderFilled events of the pool newer than since_ms, follows the cursor, most recent first
+pub async fn query_fills_since(client: &SuiClient, pool_id: ObjectID, base_asset: String, quote_asset: String, since_ms: u64) -> Vec<SuiEvent> {
+    let query = format!("0xdee9::clob_v2::OrderFilled<{base_asset}, {quote_asset}>");
+    let mut events = vec![];
+    let mut cursor = None;
+    loop {
+        let page = client.event_api().query_events(EventFilter::MoveEventType(query.parse().unwrap()),
+                                                   cursor, None,
+                                                   true).await;
+        if page.is_err() {
+            warn!("could not query fills, {} fetched: {:?}", events.len(), page.err());
+            break;
+        }
+        let page = page.unwrap();
+        let mut done = !page.has_next_page || page.next_cursor.is_none();
+        for item in page.data.into_iter() {
+            if item.timestamp_ms.unwrap_or(0) < since_ms {
+                done = true;
+                break;
+            }
+            // events of all pools with the same assets share the type
+            if order_filled(&item).map(|x| x.pool_id) == Some(pool_id) {
+                events.push(item);
+            }
+        }
+        if done {
+            break;
+        }
+        cursor = page.next_cursor;
+    }
+    return events;
 }
 
-#[derive(Serialize,Deserialize,Debug, Copy,Clone)]
-pub struct CalculationResult {
-    pub duration: u64,
-    pub filled_total: u64,
-    pub unfilled_total: u64,
-    pub filled_per_s: u64,
-    pub n: u64
+// typed OrderFilled event, None for other or undecodable events
+pub fn order_filled(event: &SuiEvent) -> Option<OrderFilledEvent> {
+    return match decode_event(event)?.event {
+        DeepBookEvent::OrderFilled(x) => Some(x),
+        _ => None,
+    };
 }
 
-pub fn calculate(events: &Vec<SuiEvent>) -> CalculationResult{
-    let length = events.len();
-    let t = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis();
-    let max_time = u128::from(events.last().clone().unwrap().timestamp_ms.unwrap());
-    debug!("{} {}", max_time, t);
-    let duration = (t - max_time)/ 1000;
-    let mut filled_total: u128 = 0;
-    let mut unfilled_total: u128 = 0;
-    for item in events.iter(){
-        let price = u128::from_str(item.parsed_json.get("price").unwrap().as_str().unwrap()).unwrap();
-        let base_asset_quantity_filled = u128::from_str(item.parsed_json.get("base_asset_quantity_filled").unwrap().as_str().unwrap()).unwrap();
-        filled_total += (base_asset_quantity_filled * price)/(u128::from(SUI_USDC_DECIMALS) * u128::from(SUI_DECIMALS));
-        let base_asset_quantity_remaining = u128::from_str(item.parsed_json.get("base_asset_quantity_remaining").unwrap().as_str().unwrap()).unwrap();
-        unfilled_total += (base_asset_quantity_remaining * price)/(u128::from(SUI_USDC_DECIMALS) * u128::from(SUI_DECIMALS));
-        // println!("{} {} {}", item.timestamp_ms.unwrap(), price, base_asset_quantity_filled);
+// volume weighted price of fills in deepbook price units, (vwap, min price, max price)
+pub fn vwap(events: &Vec<SuiEvent>) -> Option<(f64, u64, u64)> {
+    let mut notional: u128 = 0;
+    let mut quantity: u128 = 0;
+    let mut min_price = u64::MAX;
+    let mut max_price = 0;
+    for item in events.iter() {
+        let filled = order_filled(item);
+        if filled.is_none() {
+            continue;
+        }
+        let filled = filled.unwrap();
+        let price = filled.price;
+        let base_asset_quantity_filled = u128::from(filled.base_asset_quantity_filled);
+        notional += u128::from(price) * base_asset_quantity_filled;
+        quantity += base_asset_quantity_filled;
+        min_price = min_price.min(price);
+        max_price = max_price.max(price);
     }
-    // Filled per second is amount of dollars exchanged per second ...
-    debug!("Duration: {}, Filled total: {}, Unfilled total: {}, Filled per second: {}", duration, filled_total, unfilled_total, filled_total/duration);
-    return CalculationResult{
-        duration: duration as u64,
-        filled_total: filled_total as u64,
-        unfilled_total: unfilled_total as u64,
-        filled_per_s: (filled_total / duration ) as u64, // HOW MUCH QUOTED IN QUOTE WAS EXCHANGED PER SECOND ...
-        n: length as u64,
+    if quantity == 0 {
+        return None;
     }
+    return Some((notional as f64 / quantity as f64, min_price, max_price));
 }
\ No newline at end of file
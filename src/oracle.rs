@@ -0,0 +1,196 @@
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use log::{debug, warn};
+use crate::constant::{SUI_PRICE_FEED, USDC_PRICE_FEED};
+use crate::pyth::{LatestPriceFeeds, PriceFeed, PythFeeder};
+
+// limits a price has to satisfy before the bot is allowed to quote off it
+#[derive(Debug, Clone, Copy)]
+pub struct OracleConfig {
+    // maximum age of publish_time in seconds
+    pub max_age_s: i64,
+    // maximum conf / price
+    pub max_conf_ratio: f64,
+    // maximum relative difference between oracle price and deepbook mid
+    pub max_mid_deviation: f64,
+}
+
+impl OracleConfig {
+    // for a price taken from the deepbook mid, comparing it with the mid would always pass
+    pub fn without_mid_check(&self) -> OracleConfig {
+        return OracleConfig{ max_mid_deviation: f64::INFINITY, ..*self }
+    }
+}
+
+impl Default for OracleConfig {
+    fn default() -> Self {
+        return OracleConfig{ max_age_s: 30, max_conf_ratio: 0.01, max_mid_deviation: 0.02 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OracleError {
+    Unavailable,
+    MissingFeed(String),
+    InvalidNumber(String),
+    NonPositive(f64),
+    Stale { age_s: i64, max_age_s: i64 },
+    WideConfidence { ratio: f64, max_ratio: f64 },
+    MidDeviation { deviation: f64, max_deviation: f64 },
+}
+
+// price which passed staleness and confidence checks, already scaled by expo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValidatedPrice {
+    pub price: f64,
+    pub conf: f64,
+    pub ema_price: f64,
+    pub ema_conf: f64,
+    pub publish_time: i64,
+}
+
+impl ValidatedPrice {
+    pub fn conf_ratio(&self) -> f64 {
+        return self.conf / self.price;
+    }
+}
+
+// hermes returns ids without 0x prefix, ids in constant.rs have it
+pub fn normalize_feed_id(id: &str) -> String {
+    return id.trim_start_matches("0x").to_lowercase();
+}
+
+pub fn find_feed<'a>(feeds: &'a LatestPriceFeeds, id: &str) -> Option<&'a PriceFeed> {
+    let wanted = normalize_feed_id(id);
+    return feeds.iter().find(|x| normalize_feed_id(&x.id) == wanted);
+}
+
+// converts pyth integer representation to f64 i.e. price * 10^expo
+pub fn scale(value: &str, expo: i64) -> Result<f64, OracleError> {
+    let raw = f64::from_str(value).map_err(|_| OracleError::InvalidNumber(value.to_string()))?;
+    return Ok(raw * f64::powi(10.0, expo as i32));
+}
+
+pub fn validate_feed(feed: &PriceFeed, config: &OracleConfig, now_s: i64) -> Result<ValidatedPrice, OracleError> {
+    let price = scale(&feed.price.price, feed.price.expo)?;
+    let conf = scale(&feed.price.conf, feed.price.expo)?;
+    let ema_price = scale(&feed.ema_price.price, feed.ema_price.expo)?;
+    let ema_conf = scale(&feed.ema_price.conf, feed.ema_price.expo)?;
+    if price <= 0.0 {
+        return Err(OracleError::NonPositive(price));
+    }
+    let age_s = now_s - feed.price.publish_time;
+    if age_s > config.max_age_s {
+        return Err(OracleError::Stale { age_s, max_age_s: config.max_age_s });
+    }
+    let ratio = conf / price;
+    if ratio > config.max_conf_ratio {
+        return Err(OracleError::WideConfidence { ratio, max_ratio: config.max_conf_ratio });
+    }
+    return Ok(ValidatedPrice{ price, conf, ema_price, ema_conf, publish_time: feed.price.publish_time });
+}
+
+// base/quote price from two prices in the same unit (i.e. both in USD),
+// confidence intervals are added relatively, publish time is the older one
+pub fn combine(base: &ValidatedPrice, quote: &ValidatedPrice) -> ValidatedPrice {
+    let price = base.price / quote.price;
+    let ema_price = base.ema_price / quote.ema_price;
+    return ValidatedPrice{
+        price,
+        conf: price * (base.conf_ratio() + quote.conf_ratio()),
+        ema_price,
+        ema_conf: ema_price * (base.ema_conf / base.ema_price + quote.ema_conf / quote.ema_price),
+        publish_time: base.publish_time.min(quote.publish_time),
+    }
+}
+
+pub fn validate_pair(feeds: &LatestPriceFeeds, base_feed: &str, quote_feed: &str, config: &OracleConfig, now_s: i64) -> Result<ValidatedPrice, OracleError> {
+    let base = validate_feed(find_feed(feeds, base_feed).ok_or(OracleError::MissingFeed(base_feed.to_string()))?, config, now_s)?;
+    let quote = validate_feed(find_feed(feeds, quote_feed).ok_or(OracleError::MissingFeed(quote_feed.to_string()))?, config, now_s)?;
+    let result = combine(&base, &quote);
+    if result.conf_ratio() > config.max_conf_ratio {
+        return Err(OracleError::WideConfidence { ratio: result.conf_ratio(), max_ratio: config.max_conf_ratio });
+    }
+    debug!("validated price: {:?}", result);
+    return Ok(result);
+}
+
+pub fn validate_sui_usdc(feeds: &LatestPriceFeeds, config: &OracleConfig, now_s: i64) -> Result<ValidatedPrice, OracleError> {
+    return validate_pair(feeds, SUI_PRICE_FEED, USDC_PRICE_FEED, config, now_s);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeedPair {
+    // single feed quoting base asset directly in quote asset
+    Direct(String),
+    // two X/USD feeds, price is base / quote
+    ThroughUsd { base: String, quote: String },
+}
+
+impl FeedPair {
+    pub fn sui_usdc() -> FeedPair {
+        return FeedPair::ThroughUsd { base: SUI_PRICE_FEED.to_string(), quote: USDC_PRICE_FEED.to_string() }
+    }
+
+    pub fn feed_ids(&self) -> Vec<String> {
+        match self {
+            FeedPair::Direct(id) => vec![id.clone()],
+            FeedPair::ThroughUsd { base, quote } => vec![base.clone(), quote.clone()],
+        }
+    }
+}
+
+pub fn price_for_pair(pair: &FeedPair, feeds: &LatestPriceFeeds, config: &OracleConfig, now_s: i64) -> Result<ValidatedPrice, OracleError> {
+    match pair {
+        FeedPair::Direct(id) => {
+            let feed = find_feed(feeds, id).ok_or(OracleError::MissingFeed(id.clone()))?;
+            return validate_feed(feed, config, now_s);
+        }
+        FeedPair::ThroughUsd { base, quote } => {
+            return validate_pair(feeds, base, quote, config, now_s);
+        }
+    }
+}
+
+// validated base/quote price for any pair of pyth feeds
+pub struct PriceOracle {
+    feeder: PythFeeder,
+    pub pair: FeedPair,
+    pub config: OracleConfig,
+}
+
+impl PriceOracle {
+    pub fn new(pair: FeedPair, config: OracleConfig) -> PriceOracle {
+        return PriceOracle{ feeder: PythFeeder::new(pair.feed_ids()), pair, config }
+    }
+
+    pub fn new_suiusdc(config: OracleConfig) -> PriceOracle {
+        return PriceOracle::new(FeedPair::sui_usdc(), config);
+    }
+
+    pub fn price_from_feeds(&self, feeds: &LatestPriceFeeds, now_s: i64) -> Result<ValidatedPrice, OracleError> {
+        return price_for_pair(&self.pair, feeds, &self.config, now_s);
+    }
+
+    pub async fn get_price(&self) -> Result<ValidatedPrice, OracleError> {
+        let feeds = self.feeder.get_latest_price().await.ok_or(OracleError::Unavailable)?;
+        let t = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+        return self.price_from_feeds(&feeds, t);
+    }
+}
+
+// compares oracle price with deepbook mid, best_bid and best_ask as returned by get_market_price,
+// price_decimals is the deepbook price scaling i.e. SUI_USDC_DECIMALS
+pub fn check_mid_deviation(price: f64, best_bid: u64, best_ask: u64, price_decimals: u64, config: &OracleConfig) -> Result<f64, OracleError> {
+    // empty side of the book, nothing to compare against
+    if best_bid == 0 || best_ask == 0 {
+        return Ok(0.0);
+    }
+    let mid = ((best_bid + best_ask) as f64 / 2.0) / price_decimals as f64;
+    let deviation = (price - mid).abs() / mid;
+    if deviation > config.max_mid_deviation {
+        warn!("oracle price {} deviates from deepbook mid {} by {}", price, mid, deviation);
+        return Err(OracleError::MidDeviation { deviation, max_deviation: config.max_mid_deviation });
+    }
+    return Ok(deviation);
+}
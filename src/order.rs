@@ -1,7 +1,7 @@
 use std::str::FromStr;
 use std::time::{SystemTime, UNIX_EPOCH};
 use bcs::from_bytes;
-use log::debug;
+use log::{debug, warn};
 use sui_sdk::rpc_types::SuiObjectDataOptions;
 use sui_sdk::SuiClient;
 use sui_types::base_types::{ObjectID, ObjectRef, SequenceNumber, SuiAddress};
@@ -12,8 +12,8 @@ use sui_types::TypeTag;
 use crate::constant::DEEPBOOK_PKG;
 use serde::{Serialize,Deserialize};
 use crate::market::get_market_price;
-use crate::user::get_account_balance;
-use crate::utils::parse_result_u64;
+use crate::user::{deposit, get_account_balance, withdraw};
+use crate::utils::{parse_result_option_u64, parse_result_u64};
 
 #[derive(Serialize,Deserialize,Debug)]
 pub struct OrderPage {
@@ -29,18 +29,18 @@ pub struct Order {
     pub order_id: u64,
     pub client_order_id: u64,
     pub price: u64,
-    original_quantity: u64,
-    quantity: u64,
-    is_bid: bool,
-    owner: SuiAddress,
+    pub original_quantity: u64,
+    pub quantity: u64,
+    pub is_bid: bool,
+    pub owner: SuiAddress,
     pub expire_timestamp: u64,
-    self_matching_prevention: u8
+    pub self_matching_prevention: u8
 }
 
 #[derive(Clone)]
 pub struct OrderWrapper<'a> {
     client: &'a SuiClient,
-    pool_id: ObjectID,
+    pub pool_id: ObjectID,
     pool_initial_shared_sequence: SequenceNumber,
     cap_id: ObjectID,
     max_min: u64,
@@ -149,6 +149,42 @@ impl  OrderWrapper<'_> {
                           TypeTag::from_str(&*self.quote_asset).unwrap(),self.pool_id, self.pool_initial_shared_sequence, account_cap_ref)
     }
 
+    pub fn cancel_order(&self, mut tb: ProgrammableTransactionBuilder, order_id: u64, account_cap_ref: ObjectRef) -> ProgrammableTransactionBuilder {
+        return cancel_order(tb,
+                            TypeTag::from_str(&*self.base_asset).unwrap(),
+                            TypeTag::from_str(&*self.quote_asset).unwrap(),
+                            self.pool_id,
+                            self.pool_initial_shared_sequence,
+                            order_id,
+                            account_cap_ref);
+    }
+
+    // if coin is not provided, quantity is split from gas coin (only for SUI)
+    pub fn deposit(&self, mut tb: ProgrammableTransactionBuilder, coin: Option<ObjectRef>, quantity: u64, is_base: bool, account_cap_ref: ObjectRef) -> ProgrammableTransactionBuilder {
+        return deposit(tb,
+                       TypeTag::from_str(&*self.base_asset).unwrap(),
+                       TypeTag::from_str(&*self.quote_asset).unwrap(),
+                       self.pool_id,
+                       self.pool_initial_shared_sequence,
+                       coin,
+                       quantity,
+                       is_base,
+                       account_cap_ref);
+    }
+
+    // withdrawn coin is transferred to recipient
+    pub fn withdraw(&self, mut tb: ProgrammableTransactionBuilder, quantity: u64, is_base: bool, account_cap_ref: ObjectRef, recipient: SuiAddress) -> ProgrammableTransactionBuilder {
+        return withdraw(tb,
+                        TypeTag::from_str(&*self.base_asset).unwrap(),
+                        TypeTag::from_str(&*self.quote_asset).unwrap(),
+                        self.pool_id,
+                        self.pool_initial_shared_sequence,
+                        quantity,
+                        is_base,
+                        account_cap_ref,
+                        recipient);
+    }
+
     pub fn get_market_price(self, mut tb: ProgrammableTransactionBuilder) -> ProgrammableTransactionBuilder {
         return get_market_price(tb,                                      TypeTag::from_str(&*self.base_asset).unwrap(),
                                 TypeTag::from_str(&*self.quote_asset).unwrap(), self.pool_id)
@@ -177,14 +213,44 @@ impl  OrderWrapper<'_> {
     }
 
     pub async fn get_data(&self) -> (Vec<u64>, Vec<u64>, Vec<Order>){
+        return self.try_get_data().await.expect("could not fetch pool data");
+    }
+
+    // same as get_data, None if the rpc call fails
+    pub async fn try_get_data(&self) -> Option<(Vec<u64>, Vec<u64>, Vec<Order>)> {
         let mut tb = ProgrammableTransactionBuilder::new();
         let tb = self.add_transactions(tb);
         let response = self.client.read_api().dev_inspect_transaction_block(SuiAddress::ZERO, TransactionKind::ProgrammableTransaction(tb.finish()), None, None, None).await;
-        let results = response.unwrap().results.unwrap();
+        if response.is_err() {
+            warn!("could not fetch pool data: {:?}", response.err());
+            return None;
+        }
+        let results = response.unwrap().results?;
         let account_balance_results = parse_result_u64(&results[0], 0);
         let market_price_results = parse_result_u64(&results[1], 1);
         let orders: Vec<Order> = from_bytes(&results[2].return_values[0].0).unwrap();
-        return (account_balance_results, market_price_results, orders);
+        return Some((account_balance_results, market_price_results, orders));
+    }
+
+    // (best_bid_price, best_ask_price), 0 if side of the book is empty
+    pub async fn get_best_bid_ask(&self) -> Vec<u64> {
+        return self.try_get_best_bid_ask().await.expect("could not fetch best bid and ask");
+    }
+
+    // same as get_best_bid_ask, None if the rpc call fails
+    pub async fn try_get_best_bid_ask(&self) -> Option<Vec<u64>> {
+        let tb = ProgrammableTransactionBuilder::new();
+        let tb = get_market_price(tb,
+                                  TypeTag::from_str(&*self.base_asset).unwrap(),
+                                  TypeTag::from_str(&*self.quote_asset).unwrap(),
+                                  self.pool_id);
+        let response = self.client.read_api().dev_inspect_transaction_block(SuiAddress::ZERO, TransactionKind::ProgrammableTransaction(tb.finish()), None, None, None).await;
+        if response.is_err() {
+            warn!("could not fetch best bid and ask: {:?}", response.err());
+            return None;
+        }
+        let results = response.unwrap().results?;
+        return Some(parse_result_option_u64(&results[0]).iter().map(|x| x.unwrap_or(0)).collect());
     }
 
     pub async fn get_bid_ask(&self) -> (OrderPage, OrderPage) {
@@ -347,7 +413,7 @@ pub fn cancel_order(mut tb: ProgrammableTransactionBuilder, baseAsset: TypeTag,
     tb.move_call(
         DEEPBOOK_PKG.parse().unwrap(),
         "clob_v2".parse().unwrap(),
-        "cancel_all_orders".parse().unwrap(),
+        "cancel_order".parse().unwrap(),
         vec![baseAsset, quoteAsset],
         vec![
             CallArg::Object(pool_object),
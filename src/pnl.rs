@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use serde_derive::{Deserialize, Serialize};
+use sui_types::base_types::SuiAddress;
+use sui_types::event::EventID;
+use crate::constant::FLOAT_SCALING;
+use crate::events::OrderFilledEvent;
+
+pub const DAY_MS: u64 = 86_400_000;
+
+// our side of one OrderFilled event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fill {
+    pub timestamp_ms: u64,
+    // deepbook price units
+    pub price: u64,
+    pub quantity: u64,
+    // we bought base
+    pub is_buy: bool,
+    pub is_maker: bool,
+    // maker rebate received or taker commission paid in raw quote units
+    pub fee: u64,
+}
+
+// sides of OrderFilled which belong to owners, both sides are ours on self trade
+pub fn own_fills(event: &OrderFilledEvent, timestamp_ms: u64, owners: &Vec<SuiAddress>) -> Vec<Fill> {
+    let mut fills = vec![];
+    if owners.contains(&event.maker_address) {
+        fills.push(Fill{
+            timestamp_ms,
+            price: event.price,
+            quantity: event.base_asset_quantity_filled,
+            is_buy: event.is_bid,
+            is_maker: true,
+            fee: event.maker_rebates,
+        });
+    }
+    if owners.contains(&event.taker_address) {
+        fills.push(Fill{
+            timestamp_ms,
+            price: event.price,
+            quantity: event.base_asset_quantity_filled,
+            is_buy: !event.is_bid,
+            is_maker: false,
+            fee: event.taker_commission,
+        });
+    }
+    return fills;
+}
+
+// UTC date YYYY-MM-DD of timestamp
+pub fn day_of(timestamp_ms: u64) -> String {
+    let z = (timestamp_ms / DAY_MS) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 {mp + 3} else {mp - 9};
+    let year = yoe + era * 400 + if month <= 2 {1} else {0};
+    return format!("{:04}-{:02}-{:02}", year, month, day);
+}
+
+// all amounts in raw quote units
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct DailyPnl {
+    pub realized: f64,
+    pub rebates: f64,
+    pub commissions: f64,
+    pub volume: f64,
+    pub fills: u64,
+    // position and unrealized pnl at the last mark of the day
+    pub position: f64,
+    pub unrealized: f64,
+}
+
+impl DailyPnl {
+    pub fn net(&self) -> f64 {
+        return self.realized + self.rebates - self.commissions;
+    }
+}
+
+// average cost accounting of one pool, position in raw base units, pnl in raw quote units
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PnlEngine {
+    // positive long, negative short
+    pub position: f64,
+    // deepbook price units
+    pub average_cost: f64,
+    pub realized: f64,
+    pub rebates: f64,
+    pub commissions: f64,
+    pub days: BTreeMap<String, DailyPnl>,
+    // last OrderFilled event applied, saved in the same write as pnl so that no fill is lost or applied twice
+    #[serde(default)]
+    pub cursor: Option<EventID>,
+}
+
+impl PnlEngine {
+    // empty engine if file does not exist
+    pub fn load(path: &str) -> PnlEngine {
+        if !Path::new(path).exists() {
+            return PnlEngine::default();
+        }
+        let content = fs::read_to_string(path).expect("could not read pnl file");
+        return serde_json::from_str(&content).expect("could not parse pnl file");
+    }
+
+    pub fn save(&self, path: &str) {
+        fs::write(path, serde_json::to_string_pretty(self).unwrap()).expect("could not write pnl file");
+    }
+
+    // realized pnl of the fill without fees
+    pub fn apply(&mut self, fill: &Fill) -> f64 {
+        let signed = if fill.is_buy {fill.quantity as f64} else {-(fill.quantity as f64)};
+        let price = fill.price as f64;
+        let mut realized = 0.0;
+        if self.position == 0.0 || self.position.signum() == signed.signum() {
+            self.average_cost = (self.position.abs() * self.average_cost + signed.abs() * price) / (self.position.abs() + signed.abs());
+        } else {
+            let closed = signed.abs().min(self.position.abs());
+            realized = closed * (price - self.average_cost) * self.position.signum() / FLOAT_SCALING as f64;
+            // flipped, rest of the fill opens new position at fill price
+            if signed.abs() > self.position.abs() {
+                self.average_cost = price;
+            }
+        }
+        self.position += signed;
+        if self.position == 0.0 {
+            self.average_cost = 0.0;
+        }
+        self.realized += realized;
+        let day = self.days.entry(day_of(fill.timestamp_ms)).or_default();
+        day.realized += realized;
+        day.volume += fill.quantity as f64 * price / FLOAT_SCALING as f64;
+        day.fills += 1;
+        day.position = self.position;
+        if fill.is_maker {
+            self.rebates += fill.fee as f64;
+            day.rebates += fill.fee as f64;
+        } else {
+            self.commissions += fill.fee as f64;
+            day.commissions += fill.fee as f64;
+        }
+        return realized;
+    }
+
+    // mark_price in deepbook price units i.e. oracle price * price_decimals
+    pub fn unrealized(&self, mark_price: f64) -> f64 {
+        return self.position * (mark_price - self.average_cost) / FLOAT_SCALING as f64;
+    }
+
+    // realized pnl with rebates and commissions
+    pub fn net(&self) -> f64 {
+        return self.realized + self.rebates - self.commissions;
+    }
+
+    // records unrealized pnl of the day and returns total pnl
+    pub fn mark(&mut self, now_ms: u64, mark_price: f64) -> f64 {
+        let unrealized = self.unrealized(mark_price);
+        let day = self.days.entry(day_of(now_ms)).or_default();
+        day.position = self.position;
+        day.unrealized = unrealized;
+        return self.net() + unrealized;
+    }
+}
@@ -1,5 +1,18 @@
-use sui_sdk::rpc_types::{EventFilter, EventPage};
+use std::fs;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use log::{debug, info, warn};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use sui_sdk::rpc_types::{EventFilter, EventPage, SuiEvent, SuiObjectDataOptions, SuiParsedData};
 use sui_sdk::SuiClient;
+use sui_types::base_types::ObjectID;
+use crate::constant::{FLOAT_SCALING, SUI_TYPE, USDC_TYPE};
+use crate::events::{decode_event, DeepBookEvent, PoolCreatedEvent};
+use crate::market::query_fills_since;
+use crate::order::OrderWrapper;
+use crate::pnl::DAY_MS;
+use crate::stats::MarketStats;
 
 pub async fn get_pools_created(client: &SuiClient) -> EventPage {
     let response = client.event_api().query_events(
@@ -16,4 +29,313 @@ pub async fn get_pools_created(client: &SuiClient) -> EventPage {
     //     false
     // ).await.unwrap();
     return response
-}
\ No newline at end of file
+}
+
+// all PoolCreated events, follows the cursor until the last page
+pub async fn get_all_pools_created(client: &SuiClient) -> Vec<SuiEvent> {
+    let mut events = vec![];
+    let mut cursor = None;
+    loop {
+        let page = client.event_api().query_events(
+            EventFilter::MoveEventType("0xdee9::clob_v2::PoolCreated".parse().unwrap()),
+            cursor,
+            None,
+            false
+        ).await.unwrap();
+        events.extend(page.data);
+        if !page.has_next_page || page.next_cursor.is_none() {
+            break;
+        }
+        cursor = page.next_cursor;
+    }
+    debug!("PoolCreated events: {}", events.len());
+    return events;
+}
+
+// type names in events are without 0x and with full length address,
+// returns 0x prefixed type with full length address i.e. 0x00..02::sui::SUI
+pub fn normalize_type(type_name: &str) -> String {
+    let stripped = type_name.trim_start_matches("0x");
+    match stripped.split_once("::") {
+        Some((address, rest)) => format!("0x{:0>64}::{}", address.to_lowercase(), rest),
+        None => type_name.to_string(),
+    }
+}
+
+// well known symbol or the struct name i.e. ...::coin::COIN -> COIN
+pub fn symbol(type_name: &str) -> String {
+    let normalized = normalize_type(type_name);
+    if normalized == normalize_type(SUI_TYPE) {
+        return "SUI".to_string();
+    }
+    if normalized == normalize_type(USDC_TYPE) {
+        return "USDC".to_string();
+    }
+    return normalized.rsplit("::").next().unwrap().to_uppercase();
+}
+
+/*
+parsed_json of PoolCreated, numbers are strings, type names are without 0x
+{"base_asset": {"name": "<address>::<module>::<name>"}, "lot_size": "<u64>", "maker_rebate_rate": "<u64>", "pool_id": "0x<id>", "quote_asset": {"name": "<address>::<module>::<name>"}, "taker_fee_rate": "<u64>", "tick_size": "<u64>"}
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PoolRecord {
+    pub pool_id: ObjectID,
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub tick_size: u64,
+    pub lot_size: u64,
+    pub taker_fee_rate: u64,
+    pub maker_rebate_rate: u64,
+}
+
+impl PoolRecord {
+    pub fn from_json(json: &Value) -> Option<PoolRecord> {
+        let event = serde_json::from_value::<PoolCreatedEvent>(json.clone()).ok()?;
+        return Some(PoolRecord::from_created(&event));
+    }
+
+    pub fn from_created(event: &PoolCreatedEvent) -> PoolRecord {
+        return PoolRecord{
+            pool_id: event.pool_id,
+            base_asset: normalize_type(&event.base_asset.name),
+            quote_asset: normalize_type(&event.quote_asset.name),
+            tick_size: event.tick_size,
+            lot_size: event.lot_size,
+            taker_fee_rate: event.taker_fee_rate,
+            maker_rebate_rate: event.maker_rebate_rate,
+        }
+    }
+
+    pub fn from_event(event: &SuiEvent) -> Option<PoolRecord> {
+        return match decode_event(event)?.event {
+            DeepBookEvent::PoolCreated(x) => Some(PoolRecord::from_created(&x)),
+            _ => None,
+        };
+    }
+
+    pub fn base_symbol(&self) -> String {
+        return symbol(&self.base_asset);
+    }
+
+    pub fn quote_symbol(&self) -> String {
+        return symbol(&self.quote_asset);
+    }
+
+    // i.e. SUI/USDC
+    pub fn pair(&self) -> String {
+        return format!("{}/{}", self.base_symbol(), self.quote_symbol());
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PoolRegistry {
+    pub pools: Vec<PoolRecord>,
+}
+
+impl PoolRegistry {
+    pub fn from_events(events: &Vec<SuiEvent>) -> PoolRegistry {
+        let mut pools = vec![];
+        for event in events.iter() {
+            match PoolRecord::from_event(event) {
+                Some(record) => pools.push(record),
+                None => warn!("could not decode PoolCreated event: {:?}", event.parsed_json),
+            }
+        }
+        return PoolRegistry{ pools };
+    }
+
+    pub async fn fetch(client: &SuiClient) -> PoolRegistry {
+        let events = get_all_pools_created(client).await;
+        return PoolRegistry::from_events(&events);
+    }
+
+    pub fn load(path: &str) -> Option<PoolRegistry> {
+        let content = fs::read_to_string(path).ok()?;
+        return serde_json::from_str(&content).ok();
+    }
+
+    pub fn save(&self, path: &str) {
+        let result = fs::write(path, serde_json::to_string_pretty(self).unwrap());
+        if result.is_err() {
+            warn!("could not save pool registry to {}: {:?}", path, result);
+        }
+    }
+
+    // fetches all pools and caches them
+    pub async fn refresh(client: &SuiClient, path: &str) -> PoolRegistry {
+        let registry = PoolRegistry::fetch(client).await;
+        info!("fetched {} pools, caching to {}", registry.pools.len(), path);
+        registry.save(path);
+        return registry;
+    }
+
+    // uses cached registry if it was written less than max_age_s ago, otherwise refreshes it
+    pub async fn load_or_fetch(client: &SuiClient, path: &str, max_age_s: u64) -> PoolRegistry {
+        let age = fs::metadata(path).ok()
+            .and_then(|x| x.modified().ok())
+            .and_then(|x| x.elapsed().ok());
+        let cached = PoolRegistry::load(path);
+        if cached.is_some() && age.is_some() && age.unwrap().as_secs() < max_age_s {
+            return cached.unwrap();
+        }
+        return PoolRegistry::refresh(client, path).await;
+    }
+
+    pub fn get(&self, pool_id: &ObjectID) -> Option<&PoolRecord> {
+        return self.pools.iter().find(|x| &x.pool_id == pool_id);
+    }
+
+    // pair is BASE/QUOTE, each side either a symbol (SUI) or a full type (0x2::sui::SUI)
+    pub fn find_all_by_pair(&self, pair: &str) -> Vec<&PoolRecord> {
+        let (base, quote) = match pair.split_once("/") {
+            Some(x) => x,
+            None => return vec![],
+        };
+        let matches = |wanted: &str, type_name: &str| {
+            if wanted.contains("::") {
+                normalize_type(wanted) == normalize_type(type_name)
+            } else {
+                wanted.to_uppercase() == symbol(type_name)
+            }
+        };
+        return self.pools.iter().filter(|x| matches(base, &x.base_asset) && matches(quote, &x.quote_asset)).collect();
+    }
+
+    // the first created pool for the pair
+    pub fn find_by_pair(&self, pair: &str) -> Option<&PoolRecord> {
+        return self.find_all_by_pair(pair).into_iter().next();
+    }
+
+    // the only pool for the pair, symbols like USDC may stand for several coin types,
+    // then the error lists the candidates so that one is picked by full type or pool id
+    pub fn resolve_pair(&self, pair: &str) -> Result<&PoolRecord, String> {
+        let pools = self.find_all_by_pair(pair);
+        if pools.len() == 0 {
+            return Err(format!("no pool found for pair {}", pair));
+        }
+        if pools.len() > 1 {
+            let candidates: Vec<String> = pools.iter().map(|x| format!("{} {}/{}", x.pool_id, x.base_asset, x.quote_asset)).collect();
+            return Err(format!("pair {} is ambiguous, use --pool or full coin types:\n{}", pair, candidates.join("\n")));
+        }
+        return Ok(pools[0]);
+    }
+}
+
+// (tick_size, lot_size) from fields of the pool object
+pub async fn get_pool_params(client: &SuiClient, pool_id: ObjectID) -> Option<(u64, u64)> {
+    let response = client.read_api().get_object_with_options(pool_id, SuiObjectDataOptions::new().with_content()).await;
+    if response.is_err() {
+        warn!("could not fetch pool {}: {:?}", pool_id, response);
+        return None;
+    }
+    match response.unwrap().data?.content? {
+        SuiParsedData::MoveObject(object) => {
+            let json = object.fields.to_json_value();
+            let number = |key: &str| -> Option<u64> { u64::from_str(json.get(key)?.as_str()?).ok() };
+            return Some((number("tick_size")?, number("lot_size")?));
+        }
+        _ => return None,
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PoolSummary {
+    pub pool_id: ObjectID,
+    pub pair: String,
+    pub best_bid: f64,
+    pub best_ask: f64,
+    pub spread_bps: Option<f64>,
+    // in whole quote units
+    pub volume_24h: u64,
+    pub trades_24h: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PoolSort {
+    Volume,
+    Spread,
+    Pair,
+}
+
+impl FromStr for PoolSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "volume" => Ok(PoolSort::Volume),
+            "spread" => Ok(PoolSort::Spread),
+            "pair" => Ok(PoolSort::Pair),
+            other => Err(format!("unknown sort: {}", other)),
+        }
+    }
+}
+
+async fn coin_decimals(client: &SuiClient, coin_type: &str) -> u8 {
+    let metadata = client.coin_read_api().get_coin_metadata(coin_type.to_string()).await;
+    match metadata {
+        Ok(Some(x)) => x.decimals,
+        _ => {
+            warn!("no coin metadata for {}, assuming 9 decimals", coin_type);
+            9
+        }
+    }
+}
+
+// deepbook price units per whole quote per whole base, i.e. SUI_USDC_DECIMALS for 9 and 6 decimals
+pub fn price_decimals(base_decimals: u32, quote_decimals: u32) -> u64 {
+    if quote_decimals >= base_decimals {
+        return (FLOAT_SCALING as u128 * 10_u128.pow(quote_decimals - base_decimals)) as u64;
+    }
+    return (FLOAT_SCALING as u128 / 10_u128.pow(base_decimals - quote_decimals)).max(1) as u64;
+}
+
+// raw units per whole coin of base and quote and deepbook price scaling of the pool,
+// i.e. (SUI_DECIMALS, USDC_DECIMALS, SUI_USDC_DECIMALS)
+pub async fn pool_decimals(client: &SuiClient, base_asset: &str, quote_asset: &str) -> (u64, u64, u64) {
+    let base = coin_decimals(client, base_asset).await as u32;
+    let quote = coin_decimals(client, quote_asset).await as u32;
+    return (10_u64.pow(base), 10_u64.pow(quote), price_decimals(base, quote));
+}
+
+// best bid/ask from get_market_price and 24h volume from OrderFilled events
+pub async fn summarize_pool(client: &SuiClient, record: &PoolRecord) -> PoolSummary {
+    let base_decimals = coin_decimals(client, &record.base_asset).await as i32;
+    let quote_decimals = coin_decimals(client, &record.quote_asset).await as i32;
+    // raw deepbook price to quote per base
+    let price_scale = f64::powi(10.0, base_decimals - quote_decimals) / FLOAT_SCALING as f64;
+    let order_wrapper = OrderWrapper::new(client, record.pool_id, None, None).await;
+    let bid_ask = order_wrapper.get_best_bid_ask().await;
+    let (best_bid, best_ask) = (bid_ask[0] as f64 * price_scale, bid_ask[1] as f64 * price_scale);
+    let spread_bps = if bid_ask[0] > 0 && bid_ask[1] > 0 {
+        Some((best_ask - best_bid) / ((best_ask + best_bid) / 2.0) * 10_000.0)
+    } else {
+        None
+    };
+    let t = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+    let events = query_fills_since(client, record.pool_id, record.base_asset.clone(), record.quote_asset.clone(), t - DAY_MS).await;
+    let mut stats = MarketStats::new(record.pool_id, vec![DAY_MS], vec![]);
+    for event in events.iter() {
+        stats.push_event(event);
+    }
+    let day = stats.window(t, DAY_MS);
+    let (volume_24h, trades_24h) = ((day.notional / f64::powi(10.0, quote_decimals)) as u64, day.count);
+    return PoolSummary{
+        pool_id: record.pool_id,
+        pair: record.pair(),
+        best_bid,
+        best_ask,
+        spread_bps,
+        volume_24h,
+        trades_24h,
+    }
+}
+
+// volume descending, spread ascending (empty books last), pair ascending
+pub fn sort_summaries(summaries: &mut Vec<PoolSummary>, sort: PoolSort) {
+    match sort {
+        PoolSort::Volume => summaries.sort_by(|a, b| b.volume_24h.cmp(&a.volume_24h)),
+        PoolSort::Spread => summaries.sort_by(|a, b| a.spread_bps.unwrap_or(f64::MAX).partial_cmp(&b.spread_bps.unwrap_or(f64::MAX)).unwrap()),
+        PoolSort::Pair => summaries.sort_by(|a, b| a.pair.cmp(&b.pair)),
+    }
+}
@@ -1,10 +1,10 @@
-use std::str::FromStr;
 use log::{debug, warn};
 use reqwest::Client;
 use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
 use serde_derive::Deserialize;
 use serde_derive::Serialize;
 use crate::constant::{HERMES_LATES_PRICE_FEEDS, SUI_PRICE_FEED, SUI_USDC_DECIMALS, USDC_PRICE_FEED};
+use crate::oracle::{find_feed, scale};
 
 
 pub struct PythFeeder {
@@ -12,15 +12,12 @@ pub struct PythFeeder {
     price_feeds: Vec<String>,
 }
 
+// feeds are matched by id, hermes does not have to keep the order of the request
 pub fn get_sui_usdc_price(feeds: LatestPriceFeeds) -> f64 {
-    let sui_expo = f64::from(feeds[0].price.expo as i8);
-    let sui_exponent: f64 = f64::powf(10.0,sui_expo);
-    let usdc_expo = f64::from(feeds[1].price.expo as i8);
-    let usdc_exponent: f64 = f64::powf(10.0,usdc_expo);
-    let sui_price_raw = f64::from_str(&*feeds[0].price.price).unwrap();
-    let usdc_price_raw = f64::from_str(&*feeds[1].price.price).unwrap();
-    let sui_price = sui_exponent * sui_price_raw;
-    let usdc_price = usdc_exponent * usdc_price_raw;
+    let sui_feed = find_feed(&feeds, SUI_PRICE_FEED).unwrap();
+    let usdc_feed = find_feed(&feeds, USDC_PRICE_FEED).unwrap();
+    let sui_price = scale(&sui_feed.price.price, sui_feed.price.expo).unwrap();
+    let usdc_price = scale(&usdc_feed.price.price, usdc_feed.price.expo).unwrap();
     return sui_price/usdc_price
 }
 
@@ -41,8 +38,12 @@ impl PythFeeder {
             .query(&self.price_feeds.clone().iter().map(|x| ("ids[]", x.as_str())).collect::<Vec<(&str,&str)>>()) // &("ids[]", &feed.clone()))
             .headers(headers)
             .send()
-            .await
-            .unwrap();
+            .await;
+        if response.is_err() {
+            warn!("hermes request failed: {:?}", response);
+            return None;
+        }
+        let response = response.unwrap();
         match response.status() {
             reqwest::StatusCode::OK => {
                 // on success, parse our JSON to an APIResponse
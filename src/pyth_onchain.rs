@@ -0,0 +1,162 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use async_trait::async_trait;
+use bcs::from_bytes;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use sui_sdk::rpc_types::{SuiObjectDataOptions, SuiRawData};
+use sui_sdk::SuiClient;
+use sui_types::base_types::ObjectID;
+use crate::oracle::{price_for_pair, FeedPair, OracleConfig, OracleError, ValidatedPrice};
+use crate::pyth::{EmaPrice, LatestPriceFeeds, Price, PriceFeed};
+use crate::source::PriceSource;
+
+/*
+Move layout of pyth::price_info::PriceInfoObject
+struct PriceInfoObject has key, store { id: UID, price_info: PriceInfo }
+struct PriceInfo has copy, drop, store { attestation_time: u64, arrival_time: u64, price_feed: PriceFeed }
+struct PriceFeed has copy, drop, store { price_identifier: PriceIdentifier, price: Price, ema_price: Price }
+struct Price has copy, drop, store { price: I64, conf: u64, expo: I64, timestamp: u64 }
+struct I64 has copy, drop, store { negative: bool, magnitude: u64 }
+ */
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PythI64 {
+    pub negative: bool,
+    pub magnitude: u64,
+}
+
+impl PythI64 {
+    pub fn to_i64(&self) -> i64 {
+        return if self.negative {-(self.magnitude as i64)} else {self.magnitude as i64};
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PythPrice {
+    pub price: PythI64,
+    pub conf: u64,
+    pub expo: PythI64,
+    // seconds
+    pub timestamp: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PriceIdentifier {
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PythPriceFeed {
+    pub price_identifier: PriceIdentifier,
+    pub price: PythPrice,
+    pub ema_price: PythPrice,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PriceInfo {
+    pub attestation_time: u64,
+    pub arrival_time: u64,
+    pub price_feed: PythPriceFeed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PriceInfoObject {
+    pub id: ObjectID,
+    pub price_info: PriceInfo,
+}
+
+// converts on-chain feed to the same type hermes returns
+impl From<&PythPriceFeed> for PriceFeed {
+    fn from(feed: &PythPriceFeed) -> Self {
+        let id = feed.price_identifier.bytes.iter().map(|x| format!("{:02x}", x)).collect::<String>();
+        return PriceFeed{
+            id,
+            price: Price{
+                price: feed.price.price.to_i64().to_string(),
+                conf: feed.price.conf.to_string(),
+                expo: feed.price.expo.to_i64(),
+                publish_time: feed.price.timestamp as i64,
+            },
+            ema_price: EmaPrice{
+                price: feed.ema_price.price.to_i64().to_string(),
+                conf: feed.ema_price.conf.to_string(),
+                expo: feed.ema_price.expo.to_i64(),
+                publish_time: feed.ema_price.timestamp as i64,
+            },
+        }
+    }
+}
+
+pub fn decode_price_info_object(bcs_bytes: &[u8]) -> Option<PriceFeed> {
+    let decoded: Result<PriceInfoObject, _> = from_bytes(bcs_bytes);
+    if decoded.is_err() {
+        warn!("could not decode PriceInfoObject: {:?}", decoded);
+        return None;
+    }
+    return Some(PriceFeed::from(&decoded.unwrap().price_info.price_feed));
+}
+
+// reads pyth prices from PriceInfoObject shared objects, one object per feed
+pub struct PythOnChainReader<'a> {
+    client: &'a SuiClient,
+    price_info_objects: Vec<ObjectID>,
+}
+
+impl PythOnChainReader<'_> {
+    pub fn new(client: &SuiClient, price_info_objects: Vec<ObjectID>) -> PythOnChainReader {
+        return PythOnChainReader{ client, price_info_objects }
+    }
+
+    pub async fn get_latest_price(&self) -> Option<LatestPriceFeeds> {
+        let response = self.client.read_api().multi_get_object_with_options(
+            self.price_info_objects.clone(),
+            SuiObjectDataOptions::new().with_bcs(),
+        ).await;
+        if response.is_err() {
+            warn!("could not read PriceInfoObjects: {:?}", response);
+            return None;
+        }
+        let mut feeds = vec![];
+        for item in response.unwrap().iter() {
+            let bcs = item.data.as_ref().and_then(|x| x.bcs.clone());
+            match bcs {
+                Some(SuiRawData::MoveObject(object)) => {
+                    let feed = decode_price_info_object(&object.bcs_bytes)?;
+                    debug!("on-chain feed: {:?}", feed);
+                    feeds.push(feed);
+                }
+                _ => {
+                    warn!("PriceInfoObject without move object bcs: {:?}", item);
+                    return None;
+                }
+            }
+        }
+        return Some(feeds);
+    }
+}
+
+// validated base/quote price from on-chain pyth objects
+pub struct OnChainPriceSource<'a> {
+    reader: PythOnChainReader<'a>,
+    pair: FeedPair,
+    config: OracleConfig,
+}
+
+impl OnChainPriceSource<'_> {
+    pub fn new(reader: PythOnChainReader, pair: FeedPair, config: OracleConfig) -> OnChainPriceSource {
+        return OnChainPriceSource{ reader, pair, config }
+    }
+}
+
+#[async_trait]
+impl PriceSource for OnChainPriceSource<'_> {
+    fn name(&self) -> String {
+        return "pyth-onchain".to_string();
+    }
+
+    async fn get_price(&self) -> Result<ValidatedPrice, OracleError> {
+        let feeds = self.reader.get_latest_price().await.ok_or(OracleError::Unavailable)?;
+        let t = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+        return price_for_pair(&self.pair, &feeds, &self.config, t);
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::VecDeque;
+use log::warn;
+use serde_derive::{Deserialize, Serialize};
+use crate::calculations::Result as Quotes;
+use crate::constant::FLOAT_SCALING;
+use crate::order::Order;
+
+// notional limits are in raw quote units i.e. 1_000_000 is 1 USDC
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct RiskConfig {
+    pub max_order_notional: u64,
+    // open orders plus new order on one side
+    pub max_open_notional: u64,
+    // maximum relative distance of order price from oracle price
+    pub max_oracle_deviation: f64,
+    // maximum relative distance of order price from deepbook mid
+    pub max_mid_deviation: f64,
+    pub max_orders_per_minute: usize,
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        return RiskConfig{
+            max_order_notional: 1_000_000_000,
+            max_open_notional: 2_000_000_000,
+            max_oracle_deviation: 0.05,
+            max_mid_deviation: 0.05,
+            max_orders_per_minute: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrderRequest {
+    pub price: u64,
+    pub quantity: u64,
+    pub is_bid: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RiskRejection {
+    ZeroQuantity,
+    OrderNotional { notional: u64, max: u64 },
+    OpenNotional { is_bid: bool, notional: u64, max: u64 },
+    OracleBand { deviation: f64, max: f64 },
+    MidBand { deviation: f64, max: f64 },
+    TickSize { price: u64, tick_size: u64 },
+    LotSize { quantity: u64, lot_size: u64 },
+    RateLimit { orders: usize, max: usize },
+}
+
+// raw quote units of quantity base units at raw deepbook price
+pub fn notional(price: u64, quantity: u64) -> u64 {
+    return (price as u128 * quantity as u128 / FLOAT_SCALING as u128) as u64;
+}
+
+// pre-trade checks of one pool, tick_size and lot_size as in PoolCreated
+pub struct RiskChecker {
+    pub config: RiskConfig,
+    pub tick_size: u64,
+    pub lot_size: u64,
+    // deepbook price scaling i.e. SUI_USDC_DECIMALS
+    pub price_decimals: u64,
+    // timestamps of accepted orders in the last minute
+    placed: VecDeque<u64>,
+}
+
+impl RiskChecker {
+    pub fn new(config: RiskConfig, tick_size: u64, lot_size: u64, price_decimals: u64) -> RiskChecker {
+        return RiskChecker{ config, tick_size, lot_size, price_decimals, placed: VecDeque::new() }
+    }
+
+    fn orders_last_minute(&self, now_ms: u64) -> usize {
+        return self.placed.iter().filter(|x| now_ms.saturating_sub(**x) < 60_000).count();
+    }
+
+    // checks one order against the book state, pending are orders going to the same transaction
+    pub fn check(&self, order: &OrderRequest, pending: &Vec<OrderRequest>, open_orders: &Vec<Order>,
+                 oracle_price: Option<f64>, best_bid: u64, best_ask: u64, now_ms: u64) -> Result<(), RiskRejection> {
+        if order.quantity == 0 {
+            return Err(RiskRejection::ZeroQuantity);
+        }
+        if self.tick_size > 0 && order.price % self.tick_size != 0 {
+            return Err(RiskRejection::TickSize { price: order.price, tick_size: self.tick_size });
+        }
+        if self.lot_size > 0 && order.quantity % self.lot_size != 0 {
+            return Err(RiskRejection::LotSize { quantity: order.quantity, lot_size: self.lot_size });
+        }
+        let order_notional = notional(order.price, order.quantity);
+        if order_notional > self.config.max_order_notional {
+            return Err(RiskRejection::OrderNotional { notional: order_notional, max: self.config.max_order_notional });
+        }
+        let open: u64 = open_orders.iter().filter(|x| x.is_bid == order.is_bid).map(|x| notional(x.price, x.quantity)).sum();
+        let queued: u64 = pending.iter().filter(|x| x.is_bid == order.is_bid).map(|x| notional(x.price, x.quantity)).sum();
+        let open_notional = open + queued + order_notional;
+        if open_notional > self.config.max_open_notional {
+            return Err(RiskRejection::OpenNotional { is_bid: order.is_bid, notional: open_notional, max: self.config.max_open_notional });
+        }
+        let price = order.price as f64 / self.price_decimals as f64;
+        if oracle_price.is_some() {
+            let deviation = (price - oracle_price.unwrap()).abs() / oracle_price.unwrap();
+            if deviation > self.config.max_oracle_deviation {
+                return Err(RiskRejection::OracleBand { deviation, max: self.config.max_oracle_deviation });
+            }
+        }
+        // empty side of the book, no mid
+        if best_bid > 0 && best_ask > 0 {
+            let mid = ((best_bid + best_ask) as f64 / 2.0) / self.price_decimals as f64;
+            let deviation = (price - mid).abs() / mid;
+            if deviation > self.config.max_mid_deviation {
+                return Err(RiskRejection::MidBand { deviation, max: self.config.max_mid_deviation });
+            }
+        }
+        let orders = self.orders_last_minute(now_ms) + pending.len() + 1;
+        if orders > self.config.max_orders_per_minute {
+            return Err(RiskRejection::RateLimit { orders, max: self.config.max_orders_per_minute });
+        }
+        return Ok(());
+    }
+
+    pub fn record(&mut self, count: usize, now_ms: u64) {
+        for _ in 0..count {
+            self.placed.push_back(now_ms);
+        }
+        while self.placed.front().is_some() && now_ms.saturating_sub(*self.placed.front().unwrap()) >= 60_000 {
+            self.placed.pop_front();
+        }
+    }
+
+    // checks bid and ask of quotes, accepted orders are counted for the rate limit
+    pub fn check_quotes(&mut self, quotes: &Quotes, open_orders: &Vec<Order>, oracle_price: Option<f64>,
+                        bid_ask_data: &Vec<u64>, now_ms: u64) -> Result<(), RiskRejection> {
+        let mut orders = vec![];
+        if quotes.bid_quantity > 0 {
+            orders.push(OrderRequest{ price: quotes.bid_price, quantity: quotes.bid_quantity, is_bid: true });
+        }
+        if quotes.ask_quantity > 0 {
+            orders.push(OrderRequest{ price: quotes.ask_price, quantity: quotes.ask_quantity, is_bid: false });
+        }
+        let mut pending = vec![];
+        for order in orders.iter() {
+            let result = self.check(order, &pending, open_orders, oracle_price, bid_ask_data[0], bid_ask_data[1], now_ms);
+            if result.is_err() {
+                warn!("risk check rejected {:?}: {:?}", order, result);
+                return result;
+            }
+            pending.push(*order);
+        }
+        self.record(pending.len(), now_ms);
+        return Ok(());
+    }
+}
@@ -0,0 +1,676 @@
+use std::fs;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use futures_util::future::join_all;
+use log::{debug, info, warn};
+use serde_derive::{Deserialize, Serialize};
+use sui_sdk::{SuiClient, SuiClientBuilder};
+use sui_types::base_types::{ObjectID, ObjectRef, SuiAddress};
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+use crate::calculations::{anchor_price, calculate_oracle_totals, PriceAnchor, QuoteOptions};
+use crate::calculations::Result as Quotes;
+use crate::candles::CandleBuilder;
+use crate::breaker::{BreakerConfig, CircuitBreaker, KillSwitch};
+use crate::fills::{load_cursor, FillStream};
+use crate::gas::GasPool;
+use crate::inventory::{apply_inventory_limits, flatten_order, inventory_state, wallet_balances, Inventory, InventoryConfig, InventoryState};
+use crate::constant::{LIMIT_ORDER_IMMEDIATE_OR_CANCEL, LIMIT_ORDER_POST_OR_ABORT, SUI_DECIMALS, SUI_PRICE_FEED, SUI_USDC_DECIMALS, USDC_DECIMALS, USDC_PRICE_FEED};
+use crate::pnl::{own_fills, PnlEngine};
+use crate::oracle::{check_mid_deviation, FeedPair, OracleConfig, ValidatedPrice};
+use crate::order::{Order, OrderWrapper};
+use crate::pool::get_pool_params;
+use crate::pyth::PythFeeder;
+use crate::pyth_onchain::{OnChainPriceSource, PythOnChainReader};
+use crate::risk::{OrderRequest, RiskChecker, RiskConfig};
+use crate::source::{DeepBookMidSource, DeepBookVwapSource, PriceAggregator, PriceSource, SharedFeedsSource};
+use crate::transaction::{SubmitResult, TransactionWrapper};
+use crate::stats::{MarketSnapshot, MarketStats, MINUTE_MS};
+use crate::subscriber::feed_pools_stats;
+use crate::user::get_account_cap_owner;
+use crate::volatility::{EwmaVolatility, ParkinsonVolatility, PriceSample, VolatilityEstimator};
+
+fn default_price_decimals() -> u64 { SUI_USDC_DECIMALS }
+fn default_base_decimals() -> u64 { SUI_DECIMALS }
+fn default_quote_decimals() -> u64 { USDC_DECIMALS }
+fn default_anchor() -> PriceAnchor { PriceAnchor::Spot }
+fn default_weight() -> f64 { 1.0 }
+fn default_min_sources() -> usize { 1 }
+
+// one entry of the pools config file, feeds default to SUI/USD and USDC/USD
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PoolConfig {
+    pub pool: String,
+    pub base_feed: Option<String>,
+    pub quote_feed: Option<String>,
+    pub direct_feed: Option<String>,
+    #[serde(default = "default_price_decimals")]
+    pub price_decimals: u64,
+    #[serde(default = "default_base_decimals")]
+    pub base_decimals: u64,
+    #[serde(default = "default_quote_decimals")]
+    pub quote_decimals: u64,
+    #[serde(default = "default_anchor")]
+    pub anchor: PriceAnchor,
+    #[serde(default)]
+    pub conf_multiplier: f64,
+    #[serde(default)]
+    pub momentum_weight: f64,
+    // account cap name or id, the default account if not provided
+    #[serde(default)]
+    pub account: Option<String>,
+    // base inventory limits, custodian plus wallet balance
+    #[serde(default)]
+    pub inventory: Option<InventoryConfig>,
+    // weights of price sources in median as in run arguments, deepbook mid and vwap are not used by default
+    #[serde(default = "default_weight")]
+    pub pyth_weight: f64,
+    #[serde(default)]
+    pub mid_weight: f64,
+    #[serde(default)]
+    pub vwap_weight: f64,
+    #[serde(default = "default_min_sources")]
+    pub min_sources: usize,
+    // PriceInfoObjects of base (or direct) feed and quote feed, enable on-chain pyth price source
+    #[serde(default)]
+    pub onchain_base_object: Option<String>,
+    #[serde(default)]
+    pub onchain_quote_object: Option<String>,
+    #[serde(default = "default_weight")]
+    pub onchain_weight: f64,
+    // sources with 0 weight are used when weighted ones are not enough, off so that the bot stops quoting without pyth
+    #[serde(default)]
+    pub price_fallback: bool,
+}
+
+impl PoolConfig {
+    pub fn pool_id(&self) -> ObjectID {
+        return ObjectID::from_str(&self.pool).unwrap();
+    }
+
+    pub fn feed_pair(&self) -> FeedPair {
+        if self.direct_feed.is_some() {
+            return FeedPair::Direct(self.direct_feed.clone().unwrap());
+        }
+        return FeedPair::ThroughUsd {
+            base: self.base_feed.clone().unwrap_or(SUI_PRICE_FEED.to_string()),
+            quote: self.quote_feed.clone().unwrap_or(USDC_PRICE_FEED.to_string()),
+        }
+    }
+
+    pub fn onchain_objects(&self) -> Vec<ObjectID> {
+        let mut objects = vec![];
+        if self.onchain_base_object.is_some() {
+            objects.push(ObjectID::from_str(self.onchain_base_object.as_ref().unwrap()).unwrap());
+            if self.onchain_quote_object.is_some() {
+                objects.push(ObjectID::from_str(self.onchain_quote_object.as_ref().unwrap()).unwrap());
+            }
+        }
+        return objects;
+    }
+
+    pub fn quote_options(&self) -> QuoteOptions {
+        return QuoteOptions{
+            anchor: self.anchor,
+            conf_multiplier: self.conf_multiplier,
+            momentum_weight: self.momentum_weight,
+        }
+    }
+}
+
+pub fn load_pool_configs(path: &str) -> Vec<PoolConfig> {
+    let content = fs::read_to_string(path).expect("could not read pools config");
+    return serde_json::from_str(&content).expect("could not parse pools config");
+}
+
+// hermes price of the pool with on-chain pyth, deepbook mid and deepbook vwap sources weighted as in pool config
+pub async fn pool_aggregator<'a>(client: &'a SuiClient, pool: &PoolConfig, hermes: Box<dyn PriceSource + 'a>, oracle_config: OracleConfig) -> PriceAggregator<'a> {
+    let mid_wrapper = OrderWrapper::new(client, pool.pool_id(), None, None).await;
+    let (base_asset, quote_asset) = (mid_wrapper.base_asset.clone(), mid_wrapper.quote_asset.clone());
+    let mut aggregator = PriceAggregator::new(pool.min_sources)
+        .with_fallback(pool.price_fallback)
+        .add(hermes, pool.pyth_weight);
+    let onchain_objects = pool.onchain_objects();
+    if onchain_objects.len() > 0 {
+        let reader = PythOnChainReader::new(client, onchain_objects);
+        aggregator = aggregator.add(Box::new(OnChainPriceSource::new(reader, pool.feed_pair(), oracle_config)), pool.onchain_weight);
+    }
+    return aggregator
+        .add(Box::new(DeepBookMidSource::new(mid_wrapper, pool.price_decimals)), pool.mid_weight)
+        .add(Box::new(DeepBookVwapSource::new(client, base_asset, quote_asset, pool.price_decimals, oracle_config.max_age_s)), pool.vwap_weight);
+}
+
+#[derive(Debug)]
+pub enum QuoteAction {
+    Wait,
+    CancelAll,
+    Place(Quotes),
+    // cancel all and send IOC order back to inventory limit
+    Flatten(OrderRequest),
+}
+
+// oracle price is present and close enough to the deepbook mid to quote or flatten off it
+pub fn oracle_healthy(oracle_price: Option<ValidatedPrice>, bid_ask_data: &Vec<u64>, pool: &PoolConfig, oracle_config: &OracleConfig) -> bool {
+    return oracle_price.is_some() && check_mid_deviation(oracle_price.unwrap().price, bid_ask_data[0], bid_ask_data[1], pool.price_decimals, oracle_config).is_ok();
+}
+
+// what to do in the pool this tick, balance_data, bid_ask_data and open_orders as returned by OrderWrapper::get_data
+pub fn decide(balance_data: &Vec<u64>, bid_ask_data: &Vec<u64>, open_orders: &Vec<Order>,
+              oracle_price: Option<ValidatedPrice>, volatility: Option<f64>, stats: &MarketSnapshot,
+              pool: &PoolConfig, oracle_config: &OracleConfig, now_ms: u64) -> QuoteAction {
+    if volatility.is_none() {
+        return QuoteAction::Wait;
+    }
+    // do not quote off stale or deviating price, pull resting orders instead
+    if !oracle_healthy(oracle_price, bid_ask_data, pool, oracle_config) {
+        if open_orders.len() > 0 {
+            warn!("oracle unhealthy, cancelling orders in pool {}", pool.pool);
+            return QuoteAction::CancelAll;
+        }
+        return QuoteAction::Wait;
+    }
+    let validated_price = oracle_price.unwrap();
+    let options = pool.quote_options();
+    let price = anchor_price(&validated_price, &options);
+    if open_orders.len() > 0 {
+        let order = open_orders.first().unwrap();
+        let diff = (((price * pool.price_decimals as f64) - order.price as f64).abs()/order.price as f64).min((order.price as f64 - (((bid_ask_data[0] + bid_ask_data[1])/2) as f64)).abs()/order.price as f64);
+        let expires_soon = order.expire_timestamp.saturating_sub(now_ms) < 60 * 1000;
+        // if the price difference is greater than 1 percent
+        if diff > 0.01 || expires_soon {
+            info!("cancel {} {}", diff, expires_soon);
+            return QuoteAction::CancelAll;
+        }
+        debug!("Orders opened: {:?}", open_orders);
+        return QuoteAction::Wait;
+    }
+    let orders_to_do = calculate_oracle_totals(balance_data, &validated_price, &options,
+                                               Some(pool.base_decimals), Some(pool.quote_decimals), Some(pool.price_decimals),
+                                               stats, volatility.unwrap());
+    info!("Price: {} {:?} {:?} {:?}", price, volatility, stats.window(MINUTE_MS), bid_ask_data);
+    return match orders_to_do {
+        Some(quotes) => QuoteAction::Place(quotes),
+        None => QuoteAction::Wait,
+    };
+}
+
+// inventory limits on top of decide, hard limit flattens even with orders resting, soft limit quotes only the reducing side,
+// nothing is flattened off a price decide rejected
+pub fn apply_inventory(action: QuoteAction, inventory: &Inventory, balance_data: &Vec<u64>, bid_ask_data: &Vec<u64>,
+                       oracle_price: Option<ValidatedPrice>, pool: &PoolConfig, oracle_config: &OracleConfig,
+                       tick_size: u64, lot_size: u64) -> QuoteAction {
+    if pool.inventory.is_none() || !oracle_healthy(oracle_price, bid_ask_data, pool, oracle_config) {
+        return action;
+    }
+    let config = pool.inventory.unwrap();
+    let price = oracle_price.unwrap().price;
+    let state = inventory_state(inventory, price, pool.base_decimals, pool.quote_decimals, &config);
+    if matches!(state, InventoryState::HardLong(_) | InventoryState::HardShort(_)) {
+        // locked balances are freed by cancel in the same transaction
+        let custodian = vec![balance_data[0] + balance_data[1], 0, balance_data[2] + balance_data[3], 0];
+        let order = flatten_order(state, &custodian, price, pool.price_decimals, pool.base_decimals, pool.quote_decimals, tick_size, lot_size, config.flatten_slippage);
+        if order.is_some() {
+            return QuoteAction::Flatten(order.unwrap());
+        }
+    }
+    return match action {
+        QuoteAction::Place(mut quotes) => {
+            apply_inventory_limits(&mut quotes, state);
+            if quotes.bid_quantity == 0 && quotes.ask_quantity == 0 {
+                info!("inventory {:?} in pool {}, nothing to quote", state, pool.pool);
+                return QuoteAction::Wait;
+            }
+            if state != InventoryState::Normal {
+                info!("inventory {:?} in pool {}, quoting one side", state, pool.pool);
+            }
+            QuoteAction::Place(quotes)
+        }
+        other => other,
+    };
+}
+
+// custodian plus wallet inventory of pool, wallet is fetched only when pool has inventory limits
+pub async fn fetch_inventory(client: &SuiClient, order_wrapper: &OrderWrapper, owner: SuiAddress, balance_data: &Vec<u64>, pool: &PoolConfig) -> Inventory {
+    if pool.inventory.is_none() {
+        return Inventory::new(balance_data, 0, 0);
+    }
+    let (wallet_base, wallet_quote) = wallet_balances(client, owner, &order_wrapper.base_asset, &order_wrapper.quote_asset).await;
+    return Inventory::new(balance_data, wallet_base, wallet_quote);
+}
+
+// cancels resting orders and sends IOC order
+pub fn add_flatten(order_wrapper: &OrderWrapper, mut tb: ProgrammableTransactionBuilder, order: &OrderRequest, account_cap_ref: ObjectRef) -> ProgrammableTransactionBuilder {
+    tb = order_wrapper.clone().cancel_all_orders(tb, account_cap_ref);
+    return order_wrapper.place_limit_order(tb,
+                                           order.price,
+                                           order.quantity,
+                                           order.is_bid,
+                                           LIMIT_ORDER_IMMEDIATE_OR_CANCEL,
+                                           None,
+                                           account_cap_ref,
+                                           None,
+    );
+}
+
+// adds post only bid and ask from quotes
+pub fn add_quotes(order_wrapper: &OrderWrapper, mut tb: ProgrammableTransactionBuilder, quotes: &Quotes, account_cap_ref: ObjectRef, now_ms: u64) -> ProgrammableTransactionBuilder {
+    if quotes.bid_quantity > 0 {
+        tb = order_wrapper.place_limit_order(tb,
+                                             quotes.bid_price,
+                                             quotes.bid_quantity,
+                                             true,
+                                             LIMIT_ORDER_POST_OR_ABORT,
+                                             None,
+                                             account_cap_ref,
+                                             Some(now_ms + quotes.duration_ms),
+        );
+    }
+    if quotes.ask_quantity > 0 {
+        tb = order_wrapper.place_limit_order(tb,
+                                             quotes.ask_price,
+                                             quotes.ask_quantity,
+                                             false,
+                                             LIMIT_ORDER_POST_OR_ABORT,
+                                             None,
+                                             account_cap_ref,
+                                             Some(now_ms + quotes.duration_ms),
+        );
+    }
+    return tb;
+}
+
+// adds the action of the pool to tb, true if it should be paid with tip
+pub fn add_action(order_wrapper: &OrderWrapper, tb: ProgrammableTransactionBuilder, action: &QuoteAction, account_cap_ref: ObjectRef, now_ms: u64) -> (ProgrammableTransactionBuilder, bool) {
+    return match action {
+        QuoteAction::Wait => (tb, false),
+        QuoteAction::CancelAll => (order_wrapper.clone().cancel_all_orders(tb, account_cap_ref), true),
+        QuoteAction::Place(quotes) => (add_quotes(order_wrapper, tb, quotes, account_cap_ref, now_ms), false),
+        QuoteAction::Flatten(order) => (add_flatten(order_wrapper, tb, order, account_cap_ref), true),
+    };
+}
+
+// one transaction with the action of the pool, cancels and flattens are paid with tip so stale orders leave the book first
+pub async fn submit_action(transaction_wrapper: &TransactionWrapper<'_>, gas_pool: &GasPool<'_>, order_wrapper: &OrderWrapper<'_>,
+                           account_cap: ObjectID, action: &QuoteAction, now_ms: u64) -> Option<SubmitResult> {
+    if matches!(action, QuoteAction::Wait) {
+        return None;
+    }
+    let account_cap_ref = transaction_wrapper.object_ref(account_cap).await;
+    let (tb, urgent) = add_action(order_wrapper, ProgrammableTransactionBuilder::new(), action, account_cap_ref, now_ms);
+    let gasprice = if urgent {Some(transaction_wrapper.urgent_gas_price().await)} else {None};
+    return Some(transaction_wrapper.submit_ptx_with_pool(tb.finish(), gas_pool, gasprice, None).await);
+}
+
+// actions grouped by account cap, one transaction per group since the cap is an owned object,
+// urgent actions first, pools without action are dropped
+pub fn transaction_batches(account_caps: &Vec<ObjectID>, actions: Vec<(usize, QuoteAction)>) -> Vec<Vec<(usize, QuoteAction)>> {
+    let mut caps: Vec<ObjectID> = vec![];
+    let mut batches: Vec<Vec<(usize, QuoteAction)>> = vec![];
+    for (i, action) in actions.into_iter() {
+        if matches!(action, QuoteAction::Wait) {
+            continue;
+        }
+        let index = caps.iter().position(|x| *x == account_caps[i]);
+        if index.is_some() {
+            batches[index.unwrap()].push((i, action));
+        } else {
+            caps.push(account_caps[i]);
+            batches.push(vec![(i, action)]);
+        }
+    }
+    for batch in batches.iter_mut() {
+        batch.sort_by_key(|(_, x)| !matches!(x, QuoteAction::CancelAll | QuoteAction::Flatten(_)));
+    }
+    return batches;
+}
+
+// actions of pools sharing one account cap in one transaction paid with tip if any of them is urgent,
+// if it aborts every pool is sent again in its own transaction so that one crossing quote does not hold back the others,
+// returns pools with the result of their transaction
+pub async fn submit_batch(transaction_wrapper: &TransactionWrapper<'_>, gas_pool: &GasPool<'_>, order_wrappers: &Vec<OrderWrapper<'_>>,
+                          account_caps: &Vec<ObjectID>, actions: Vec<(usize, QuoteAction)>, now_ms: u64) -> Vec<(Vec<usize>, SubmitResult)> {
+    if actions.len() == 0 {
+        return vec![];
+    }
+    let account_cap_ref = transaction_wrapper.object_ref(account_caps[actions[0].0]).await;
+    let mut tb = ProgrammableTransactionBuilder::new();
+    let mut urgent = false;
+    for (i, action) in actions.iter() {
+        let added = add_action(&order_wrappers[*i], tb, action, account_cap_ref, now_ms);
+        tb = added.0;
+        urgent = urgent || added.1;
+    }
+    let gasprice = if urgent {Some(transaction_wrapper.urgent_gas_price().await)} else {None};
+    let result = transaction_wrapper.submit_ptx_with_pool(tb.finish(), gas_pool, gasprice, None).await;
+    let pools: Vec<usize> = actions.iter().map(|x| x.0).collect();
+    if actions.len() == 1 || !matches!(result, SubmitResult::MoveAbort { .. }) {
+        return vec![(pools, result)];
+    }
+    log_submit_result(&result);
+    info!("batch of pools {:?} aborted, sending every pool on its own", pools);
+    let mut results = vec![];
+    for (i, action) in actions.iter() {
+        let result = submit_action(transaction_wrapper, gas_pool, &order_wrappers[*i], account_caps[*i], action, now_ms).await;
+        if result.is_some() {
+            results.push((vec![*i], result.unwrap()));
+        }
+    }
+    return results;
+}
+
+// post only orders crossing the book are expected when the market moves, they are requoted next tick
+pub fn log_submit_result(result: &SubmitResult) {
+    match result {
+        SubmitResult::Success(response) => info!("{:?}", response.digest),
+        SubmitResult::MoveAbort { abort, .. } if abort.name.as_deref() == Some("EOrderCannotBeFullyPassive") => {
+            info!("post only order would cross the book, requoting");
+        }
+        SubmitResult::MoveAbort { abort, .. } => warn!("quote transaction aborted: {:?}", abort),
+        SubmitResult::Failed { error, .. } => warn!("quote transaction failed: {}", error),
+        other => warn!("quote transaction not executed: {:?}", other),
+    }
+}
+
+// pnl file of the pool in state_dir, written by the bot and read by the pnl command
+pub fn pnl_path(state_dir: &str, pool_id: ObjectID) -> String {
+    return format!("{}/pnl_{}.json", state_dir, pool_id);
+}
+
+// own fills and pnl of one pool, pnl and fills cursor are kept in state_dir
+pub struct PoolPnl<'a> {
+    pub stream: FillStream<'a>,
+    pub engine: PnlEngine,
+    owners: Vec<SuiAddress>,
+    pnl_path: String,
+}
+
+impl<'a> PoolPnl<'a> {
+    // fills of child caps are reported under the owner of their master cap, which is read from the cap
+    pub async fn new(client: &'a SuiClient, order_wrapper: &OrderWrapper<'_>, account_cap: ObjectID, state_dir: &str) -> PoolPnl<'a> {
+        let owner = get_account_cap_owner(client, account_cap).await
+            .unwrap_or_else(|| panic!("could not read owner of account cap {}", account_cap));
+        let owners = vec![owner];
+        let pnl_path = pnl_path(state_dir, order_wrapper.pool_id);
+        let mut engine = PnlEngine::load(&pnl_path);
+        if engine.cursor.is_none() {
+            // cursor file of earlier versions
+            engine.cursor = load_cursor(&format!("{}/fills_cursor_{}.json", state_dir, order_wrapper.pool_id));
+        }
+        let stream = FillStream::new(client, order_wrapper.pool_id, order_wrapper.base_asset.clone(), order_wrapper.quote_asset.clone(), owners.clone(), engine.cursor);
+        return PoolPnl{ stream, engine, owners, pnl_path }
+    }
+
+    // applies new fills, pnl and cursor are saved in one write
+    pub async fn update(&mut self) -> usize {
+        let fills = self.stream.poll().await;
+        for fill in fills.iter() {
+            for own in own_fills(&fill.event, fill.timestamp_ms, &self.owners).iter() {
+                self.engine.apply(own);
+            }
+        }
+        if fills.len() > 0 {
+            info!("{} fills, position: {} realized: {} net: {}", fills.len(), self.engine.position, self.engine.realized, self.engine.net());
+        }
+        if self.stream.cursor() != self.engine.cursor {
+            self.engine.cursor = self.stream.cursor();
+            self.engine.save(&self.pnl_path);
+        }
+        return fills.len();
+    }
+}
+
+// cancels orders of all pools in one transaction paid with tip
+pub async fn cancel_all_pools(transaction_wrapper: &TransactionWrapper<'_>, gas_pool: &GasPool<'_>, order_wrappers: &Vec<OrderWrapper<'_>>, account_caps: &Vec<ObjectID>) -> SubmitResult {
+    let mut tb = ProgrammableTransactionBuilder::new();
+    for (i, order_wrapper) in order_wrappers.iter().enumerate() {
+        let account_cap_ref = transaction_wrapper.object_ref(account_caps[i]).await;
+        tb = order_wrapper.clone().cancel_all_orders(tb, account_cap_ref);
+    }
+    let gasprice = transaction_wrapper.urgent_gas_price().await;
+    return transaction_wrapper.submit_ptx_with_pool(tb.finish(), gas_pool, Some(gasprice), None).await;
+}
+
+// market stats of the pool at now_ms, the book mid is sampled for fills after now_ms
+pub async fn pool_snapshot(stats: &Mutex<MarketStats>, bid_ask_data: &Vec<u64>, now_ms: u64) -> MarketSnapshot {
+    let mut guard = stats.lock().await;
+    if bid_ask_data[0] > 0 && bid_ask_data[1] > 0 {
+        guard.set_mid(now_ms, (bid_ask_data[0] + bid_ask_data[1]) as f64 / 2.0);
+    }
+    return guard.snapshot(now_ms);
+}
+
+// oracle prices per quoting volatility, spreads and limits of quoting are tuned to the dispersion over this many price ticks
+pub const VOLATILITY_SAMPLES: usize = 300;
+
+// one minute bars of oracle prices for the range based estimate
+pub const PARKINSON_BARS: usize = 15;
+
+// volatility of oracle prices for quoting, samples are timestamped by publish time so that missed ticks are accounted for,
+// the EWMA of returns is floored by the Parkinson estimate of one minute oracle candles which also sees moves between ticks that reverted
+pub struct QuoteVolatility {
+    estimator: EwmaVolatility,
+    candles: CandleBuilder,
+    parkinson: ParkinsonVolatility,
+    // start of the last bar given to parkinson
+    last_bar_ms: Option<u64>,
+    horizon_ms: f64,
+}
+
+impl QuoteVolatility {
+    pub fn new(price_interval_sec: u64) -> QuoteVolatility {
+        let interval_ms = (price_interval_sec * 1000) as f64;
+        return QuoteVolatility{
+            estimator: EwmaVolatility::riskmetrics(interval_ms, VOLATILITY_SAMPLES),
+            candles: CandleBuilder::new(MINUTE_MS, PARKINSON_BARS + 1),
+            parkinson: ParkinsonVolatility::new(PARKINSON_BARS),
+            last_bar_ms: None,
+            horizon_ms: interval_ms * VOLATILITY_SAMPLES as f64,
+        }
+    }
+
+    // a price with the same publish time as the previous one is skipped
+    pub fn update(&mut self, price: &ValidatedPrice) {
+        let timestamp_ms = price.publish_time as u64 * 1000;
+        self.estimator.update(PriceSample{ timestamp_ms, price: price.price });
+        self.candles.add_price(price);
+        for candle in self.candles.closed(timestamp_ms) {
+            if self.last_bar_ms.is_none() || candle.start_ms > self.last_bar_ms.unwrap() {
+                self.parkinson.update(candle.bar());
+                self.last_bar_ms = Some(candle.start_ms);
+            }
+        }
+    }
+
+    // standard deviation of log price change over VOLATILITY_SAMPLES ticks, None until the EWMA has enough samples
+    pub fn volatility(&self) -> Option<f64> {
+        let ewma = self.estimator.volatility_over(self.horizon_ms);
+        if ewma.is_none() {
+            return None;
+        }
+        let parkinson = self.parkinson.volatility_over(self.horizon_ms);
+        debug!("volatility ewma: {:?} parkinson: {:?}", ewma, parkinson);
+        return Some(ewma.unwrap().max(parkinson.unwrap_or(0.0)));
+    }
+}
+
+// oracle price and volatility of every pool, indexed as pools in config
+#[derive(Debug, Clone)]
+pub struct PoolMarketState {
+    pub price: Option<ValidatedPrice>,
+    // price is the deepbook mid itself
+    pub book_mid: bool,
+    pub volatility: Option<f64>,
+}
+
+// runs strategy on several pools, one hermes request for all pools per price tick with fallback sources per pool,
+// actions of pools sharing an account cap go out in one transaction, account_caps are indexed as pools
+pub async fn run_pools(client: &SuiClient, sui_rpc: String, transaction_wrapper: &TransactionWrapper<'_>, gas_pool: &GasPool<'_>, account_caps: Vec<ObjectID>,
+                       pools: Vec<PoolConfig>, oracle_config: OracleConfig, risk_config: RiskConfig,
+                       breaker_config: BreakerConfig, kill_switch: KillSwitch, state_dir: String, ws_url: Option<String>,
+                       price_interval_sec: u64, calculations_interval_sec: u64, fills_interval_sec: u64) {
+    let mut order_wrappers = vec![];
+    let mut risks = vec![];
+    let mut breakers = vec![];
+    let mut pnls = vec![];
+    let start = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+    for (i, pool) in pools.iter().enumerate() {
+        order_wrappers.push(OrderWrapper::new(client, pool.pool_id(), Some(account_caps[i]), None).await);
+        // quoting with a wrong tick or lot size would only produce aborted transactions
+        let (tick_size, lot_size) = get_pool_params(client, pool.pool_id()).await
+            .unwrap_or_else(|| panic!("could not read tick and lot size of pool {}", pool.pool_id()));
+        risks.push(RiskChecker::new(risk_config, tick_size, lot_size, pool.price_decimals));
+        breakers.push(CircuitBreaker::new(breaker_config, start));
+        pnls.push(PoolPnl::new(client, order_wrappers.last().unwrap(), account_caps[i], &state_dir).await);
+    }
+    let mut last_fills_ms = 0;
+    let empty = PoolMarketState{ price: None, book_mid: false, volatility: None };
+    let state = Arc::new(Mutex::new(vec![empty; pools.len()]));
+    let assets: Vec<(String, String)> = order_wrappers.iter().map(|x| (x.base_asset.clone(), x.quote_asset.clone())).collect();
+    let stats: Vec<Arc<Mutex<MarketStats>>> = order_wrappers.iter().map(|x| Arc::new(Mutex::new(MarketStats::with_defaults(x.pool_id)))).collect();
+    let feed_stats_of = stats.clone();
+    let price_rpc = sui_rpc.clone();
+    tokio::spawn(
+        async move {
+            let client = SuiClientBuilder::default()
+                .build(sui_rpc)
+                .await.unwrap();
+            // one subscription for all pools
+            feed_pools_stats(client, ws_url, assets, feed_stats_of, calculations_interval_sec).await;
+        }
+    );
+    let price_pools = pools.clone();
+    let price_state = Arc::clone(&state);
+    tokio::spawn(
+        async move {
+            let client = SuiClientBuilder::default()
+                .build(price_rpc)
+                .await.unwrap();
+            let mut feed_ids: Vec<String> = price_pools.iter().flat_map(|x| x.feed_pair().feed_ids()).collect();
+            feed_ids.sort();
+            feed_ids.dedup();
+            let feeder = PythFeeder::new(feed_ids);
+            let feeds = Arc::new(std::sync::Mutex::new(None));
+            let mut aggregators = vec![];
+            for pool in price_pools.iter() {
+                let hermes = SharedFeedsSource::new(Arc::clone(&feeds), pool.feed_pair(), oracle_config);
+                aggregators.push(pool_aggregator(&client, pool, Box::new(hermes), oracle_config).await);
+            }
+            let mut volatilities: Vec<QuoteVolatility> = price_pools.iter().map(|_| QuoteVolatility::new(price_interval_sec)).collect();
+            loop {
+                sleep(Duration::from_secs(price_interval_sec)).await;
+                let latest = feeder.get_latest_price().await;
+                *feeds.lock().unwrap() = latest;
+                for (i, aggregator) in aggregators.iter().enumerate() {
+                    let validated = aggregator.get_price_with_source().await;
+                    let mut guard = price_state.lock().await;
+                    if validated.is_err() {
+                        warn!("oracle unhealthy for pool {}: {:?}", i, validated);
+                        guard[i].price = None;
+                        continue;
+                    }
+                    let (price, book_mid) = validated.unwrap();
+                    if book_mid {
+                        warn!("price of pool {} is the deepbook mid, quoting without mid deviation check", i);
+                    }
+                    volatilities[i].update(&price);
+                    if let Some(vol) = volatilities[i].volatility() {
+                        guard[i].volatility = Some(vol);
+                    }
+                    guard[i].price = Some(price);
+                    guard[i].book_mid = book_mid;
+                }
+            }
+        }
+    );
+    loop {
+        sleep(Duration::from_millis(400)).await;
+        // no transaction is in flight between ticks
+        gas_pool.maintain(transaction_wrapper).await;
+        let market_state = state.lock().await.clone();
+        let t = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+        if kill_switch.is_killed() {
+            let result = cancel_all_pools(transaction_wrapper, gas_pool, &order_wrappers, &account_caps).await;
+            info!("cancelled orders of {} pools before exit", pools.len());
+            log_submit_result(&result);
+            return;
+        }
+        let poll_fills = t.saturating_sub(last_fills_ms) >= fills_interval_sec * 1000;
+        if poll_fills {
+            last_fills_ms = t;
+        }
+        let mut actions = vec![];
+        for (i, order_wrapper) in order_wrappers.iter().enumerate() {
+            if poll_fills {
+                pnls[i].update().await;
+            }
+            if breakers[i].paused(t) {
+                continue;
+            }
+            breakers[i].record_oracle(t, market_state[i].price.is_some());
+            let data = order_wrapper.try_get_data().await;
+            breakers[i].record_rpc(data.is_some());
+            if data.is_none() {
+                if breakers[i].check(t, None, 0, 0, pools[i].price_decimals).is_some() {
+                    actions.push((i, QuoteAction::CancelAll));
+                }
+                continue;
+            }
+            let (balance_data, bid_ask_data, open_orders) = data.unwrap();
+            let oracle_price = market_state[i].price.map(|x| x.price);
+            if oracle_price.is_some() {
+                pnls[i].engine.mark(t, oracle_price.unwrap() * pools[i].price_decimals as f64);
+            }
+            // realized only, marking inventory would trip on price moves alone
+            breakers[i].record_pnl(t, pnls[i].engine.net());
+            if breakers[i].check(t, oracle_price, bid_ask_data[0], bid_ask_data[1], pools[i].price_decimals).is_some() {
+                if open_orders.len() > 0 {
+                    actions.push((i, QuoteAction::CancelAll));
+                }
+                continue;
+            }
+            if market_state[i].volatility.is_none() {
+                continue;
+            }
+            let snapshot = pool_snapshot(&stats[i], &bid_ask_data, t).await;
+            let pool_oracle_config = if market_state[i].book_mid {oracle_config.without_mid_check()} else {oracle_config};
+            let action = decide(&balance_data, &bid_ask_data, &open_orders,
+                                market_state[i].price, market_state[i].volatility, &snapshot,
+                                &pools[i], &pool_oracle_config, t);
+            let inventory = fetch_inventory(client, order_wrapper, transaction_wrapper.signer, &balance_data, &pools[i]).await;
+            let action = apply_inventory(action, &inventory, &balance_data, &bid_ask_data, market_state[i].price, &pools[i],
+                                         &pool_oracle_config, risks[i].tick_size, risks[i].lot_size);
+            match action {
+                QuoteAction::Wait => {}
+                QuoteAction::Place(quotes) => {
+                    if risks[i].check_quotes(&quotes, &open_orders, oracle_price, &bid_ask_data, t).is_ok() {
+                        actions.push((i, QuoteAction::Place(quotes)));
+                    }
+                }
+                QuoteAction::Flatten(order) => {
+                    let result = risks[i].check(&order, &vec![], &vec![], oracle_price, bid_ask_data[0], bid_ask_data[1], t);
+                    if result.is_err() {
+                        warn!("risk check rejected flatten {:?}: {:?}", order, result);
+                        continue;
+                    }
+                    risks[i].record(1, t);
+                    actions.push((i, QuoteAction::Flatten(order)));
+                }
+                other => actions.push((i, other)),
+            }
+        }
+        if actions.len() == 0 {
+            continue;
+        }
+        // pools of different account caps are sent concurrently, each with its own gas coin
+        let batches = transaction_batches(&account_caps, actions);
+        let results = join_all(batches.into_iter().map(|x| submit_batch(transaction_wrapper, gas_pool, &order_wrappers, &account_caps, x, t))).await;
+        for (indexes, result) in results.into_iter().flatten() {
+            for i in indexes {
+                breakers[i].record_submit(&result);
+            }
+            log_submit_result(&result);
+        }
+    }
+}
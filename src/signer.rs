@@ -0,0 +1,210 @@
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use async_trait::async_trait;
+use bip32::DerivationPath;
+use fastcrypto::encoding::{Base64, Encoding};
+use fastcrypto::traits::ToFromBytes;
+use log::warn;
+use serde_derive::{Deserialize, Serialize};
+use shared_crypto::intent::{Intent, IntentMessage};
+use sui_keys::keystore::{AccountKeystore, FileBasedKeystore, InMemKeystore, Keystore};
+use sui_types::base_types::SuiAddress;
+use sui_types::crypto::{EncodeDecodeBase64, Signature, SignatureScheme, SuiKeyPair, SuiSignature};
+use sui_types::transaction::TransactionData;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+use tokio::time::timeout;
+
+// anything that can sign transactions of one address
+#[async_trait]
+pub trait Signer: Send + Sync {
+    fn address(&self) -> SuiAddress;
+
+    async fn sign(&self, tx_data: &TransactionData) -> Option<Signature>;
+}
+
+// ed25519, secp256k1 or secp256r1
+pub fn parse_scheme(scheme: &str) -> Option<SignatureScheme> {
+    match scheme.to_lowercase().as_str() {
+        "ed25519" => Some(SignatureScheme::ED25519),
+        "secp256k1" => Some(SignatureScheme::Secp256k1),
+        "secp256r1" => Some(SignatureScheme::Secp256r1),
+        _ => None,
+    }
+}
+
+// signs with a key from sui keystore, either in memory or sui.keystore file
+pub struct KeystoreSigner {
+    keystore: Keystore,
+    address: SuiAddress,
+}
+
+impl KeystoreSigner {
+    // derivation_path i.e. m/44'/784'/0'/0'/0', default path of the scheme if not provided
+    pub fn from_mnemonic(mnemonic: &str, scheme: SignatureScheme, derivation_path: Option<String>) -> KeystoreSigner {
+        let mut keystore = InMemKeystore::default();
+        let path = derivation_path.map(|x| DerivationPath::from_str(&x).expect("invalid derivation path"));
+        let address = keystore.import_from_mnemonic(mnemonic, scheme, path).expect("could not import mnemonic");
+        return KeystoreSigner{ keystore: Keystore::InMem(keystore), address }
+    }
+
+    // sui.keystore file as written by sui client, address defaults to the first key
+    pub fn from_keystore_file(path: &str, address: Option<SuiAddress>) -> KeystoreSigner {
+        let keystore = FileBasedKeystore::new(&PathBuf::from(path)).expect("could not read keystore");
+        let address = if address.is_some() {address.unwrap()} else {*keystore.addresses().first().expect("keystore is empty")};
+        if !keystore.addresses().contains(&address) {
+            panic!("address {} is not in keystore {}", address, path);
+        }
+        return KeystoreSigner{ keystore: Keystore::File(keystore), address }
+    }
+}
+
+#[async_trait]
+impl Signer for KeystoreSigner {
+    fn address(&self) -> SuiAddress {
+        return self.address;
+    }
+
+    async fn sign(&self, tx_data: &TransactionData) -> Option<Signature> {
+        let signature = self.keystore.sign_secure(&self.address, tx_data, Intent::sui_transaction());
+        if signature.is_err() {
+            warn!("could not sign: {:?}", signature);
+            return None;
+        }
+        return signature.ok();
+    }
+}
+
+// single private key, file contains base64 of flag || private key, same as entries of sui.keystore
+pub struct PrivateKeySigner {
+    keypair: SuiKeyPair,
+    address: SuiAddress,
+}
+
+impl PrivateKeySigner {
+    pub fn new(keypair: SuiKeyPair) -> PrivateKeySigner {
+        let address = SuiAddress::from(&keypair.public());
+        return PrivateKeySigner{ keypair, address }
+    }
+
+    pub fn from_file(path: &str) -> PrivateKeySigner {
+        let content = fs::read_to_string(path).expect("could not read key file");
+        let keypair = SuiKeyPair::decode_base64(content.trim()).expect("could not decode key file");
+        return PrivateKeySigner::new(keypair);
+    }
+}
+
+#[async_trait]
+impl Signer for PrivateKeySigner {
+    fn address(&self) -> SuiAddress {
+        return self.address;
+    }
+
+    async fn sign(&self, tx_data: &TransactionData) -> Option<Signature> {
+        return Some(Signature::new_secure(&IntentMessage::new(Intent::sui_transaction(), tx_data), &self.keypair));
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RemoteSignRequest {
+    pub address: String,
+    // base64 of bcs TransactionData
+    pub tx_bytes: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RemoteSignResponse {
+    // base64 of flag || signature || public key
+    pub signature: String,
+}
+
+// signature over tx_data by the key of address, the address is derived from the public key in the signature
+pub fn verify_signature(signature: &Signature, tx_data: &TransactionData, address: SuiAddress) -> bool {
+    let result = signature.verify_secure(&IntentMessage::new(Intent::sui_transaction(), tx_data), address, signature.scheme());
+    if result.is_err() {
+        warn!("signature does not verify for {}: {:?}", address, result);
+        return false;
+    }
+    return true;
+}
+
+// one json request per line, one json response per line
+async fn request_unix(path: &str, request: &RemoteSignRequest) -> Result<RemoteSignResponse, String> {
+    let stream = UnixStream::connect(path).await.map_err(|e| e.to_string())?;
+    let (reader, mut writer) = stream.into_split();
+    let line = serde_json::to_string(request).unwrap() + "\n";
+    writer.write_all(line.as_bytes()).await.map_err(|e| e.to_string())?;
+    let mut response = String::new();
+    BufReader::new(reader).read_line(&mut response).await.map_err(|e| e.to_string())?;
+    return serde_json::from_str(&response).map_err(|e| e.to_string());
+}
+
+async fn request_http(client: &reqwest::Client, url: &str, request: &RemoteSignRequest) -> Result<RemoteSignResponse, String> {
+    let response = client.post(url).json(request).send().await.map_err(|e| e.to_string())?;
+    if response.status() != reqwest::StatusCode::OK {
+        return Err(format!("remote signer returned {:?}", response.status()));
+    }
+    return response.json::<RemoteSignResponse>().await.map_err(|e| e.to_string());
+}
+
+// keys stay in a separate process, transaction data is posted to url i.e. http://127.0.0.1:9000/sign
+// or written to unix socket i.e. unix:///run/signer.sock, the signature is verified before it is used,
+// a signer not answering within timeout_ms fails the signing so that the quoting loop goes on
+pub struct RemoteSigner {
+    client: reqwest::Client,
+    url: String,
+    address: SuiAddress,
+    timeout_ms: u64,
+}
+
+impl RemoteSigner {
+    pub fn new(url: String, address: SuiAddress, timeout_ms: u64) -> RemoteSigner {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(timeout_ms))
+            .build()
+            .expect("could not build remote signer client");
+        return RemoteSigner{ client, url, address, timeout_ms }
+    }
+}
+
+#[async_trait]
+impl Signer for RemoteSigner {
+    fn address(&self) -> SuiAddress {
+        return self.address;
+    }
+
+    async fn sign(&self, tx_data: &TransactionData) -> Option<Signature> {
+        let request = RemoteSignRequest{
+            address: self.address.to_string(),
+            tx_bytes: Base64::encode(bcs::to_bytes(tx_data).unwrap()),
+        };
+        let response = if self.url.starts_with("unix://") {
+            let path = self.url.trim_start_matches("unix://");
+            timeout(Duration::from_millis(self.timeout_ms), request_unix(path, &request)).await
+                .unwrap_or_else(|_| Err(format!("no answer within {} ms", self.timeout_ms)))
+        } else {
+            request_http(&self.client, &self.url, &request).await
+        };
+        if response.is_err() {
+            warn!("remote signer request failed: {:?}", response);
+            return None;
+        }
+        let bytes = Base64::decode(&response.unwrap().signature);
+        if bytes.is_err() {
+            warn!("remote signature is not base64: {:?}", bytes);
+            return None;
+        }
+        let signature = Signature::from_bytes(&bytes.unwrap());
+        if signature.is_err() {
+            warn!("invalid remote signature: {:?}", signature);
+            return None;
+        }
+        let signature = signature.unwrap();
+        if !verify_signature(&signature, tx_data, self.address) {
+            return None;
+        }
+        return Some(signature);
+    }
+}
@@ -0,0 +1,220 @@
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use async_trait::async_trait;
+use log::{debug, warn};
+use sui_sdk::SuiClient;
+use crate::market::{try_query_fills, vwap};
+use crate::oracle::{price_for_pair, FeedPair, OracleConfig, OracleError, PriceOracle, ValidatedPrice};
+use crate::order::OrderWrapper;
+use crate::pyth::LatestPriceFeeds;
+
+// anything the bot can take a reference price from
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    fn name(&self) -> String;
+
+    // price is read off the deepbook book, so it cannot be checked against the book mid
+    fn is_book_mid(&self) -> bool {
+        return false;
+    }
+
+    async fn get_price(&self) -> Result<ValidatedPrice, OracleError>;
+}
+
+// pyth hermes api
+#[async_trait]
+impl PriceSource for PriceOracle {
+    fn name(&self) -> String {
+        return "pyth-hermes".to_string();
+    }
+
+    async fn get_price(&self) -> Result<ValidatedPrice, OracleError> {
+        return PriceOracle::get_price(self).await;
+    }
+}
+
+// hermes feeds fetched once per tick for several pools, validated for the pair of one pool
+pub struct SharedFeedsSource {
+    feeds: Arc<Mutex<Option<LatestPriceFeeds>>>,
+    pair: FeedPair,
+    config: OracleConfig,
+}
+
+impl SharedFeedsSource {
+    pub fn new(feeds: Arc<Mutex<Option<LatestPriceFeeds>>>, pair: FeedPair, config: OracleConfig) -> SharedFeedsSource {
+        return SharedFeedsSource{ feeds, pair, config }
+    }
+}
+
+#[async_trait]
+impl PriceSource for SharedFeedsSource {
+    fn name(&self) -> String {
+        return "pyth-hermes".to_string();
+    }
+
+    async fn get_price(&self) -> Result<ValidatedPrice, OracleError> {
+        let feeds = self.feeds.lock().unwrap().clone().ok_or(OracleError::Unavailable)?;
+        let t = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+        return price_for_pair(&self.pair, &feeds, &self.config, t);
+    }
+}
+
+// mid of best bid and best ask on deepbook, conf is half of the spread
+pub struct DeepBookMidSource<'a> {
+    order_wrapper: OrderWrapper<'a>,
+    // deepbook price scaling i.e. SUI_USDC_DECIMALS
+    price_decimals: u64,
+}
+
+impl DeepBookMidSource<'_> {
+    pub fn new(order_wrapper: OrderWrapper, price_decimals: u64) -> DeepBookMidSource {
+        return DeepBookMidSource{ order_wrapper, price_decimals }
+    }
+}
+
+#[async_trait]
+impl PriceSource for DeepBookMidSource<'_> {
+    fn name(&self) -> String {
+        return "deepbook-mid".to_string();
+    }
+
+    fn is_book_mid(&self) -> bool {
+        return true;
+    }
+
+    // always fresh, publish time is the time of the request
+    async fn get_price(&self) -> Result<ValidatedPrice, OracleError> {
+        let bid_ask = self.order_wrapper.try_get_best_bid_ask().await.ok_or(OracleError::Unavailable)?;
+        if bid_ask.len() < 2 || bid_ask[0] == 0 || bid_ask[1] == 0 {
+            return Err(OracleError::Unavailable);
+        }
+        let price = ((bid_ask[0] + bid_ask[1]) as f64 / 2.0) / self.price_decimals as f64;
+        let conf = (bid_ask[1].saturating_sub(bid_ask[0]) as f64 / 2.0) / self.price_decimals as f64;
+        let t = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+        return Ok(ValidatedPrice{ price, conf, ema_price: price, ema_conf: conf, publish_time: t });
+    }
+}
+
+// volume weighted price of the last fills on deepbook, conf is half of the fill price range,
+// stale if the last fill is older than max_age_s
+pub struct DeepBookVwapSource<'a> {
+    client: &'a SuiClient,
+    base_asset: String,
+    quote_asset: String,
+    price_decimals: u64,
+    max_age_s: i64,
+}
+
+impl DeepBookVwapSource<'_> {
+    pub fn new(client: &SuiClient, base_asset: String, quote_asset: String, price_decimals: u64, max_age_s: i64) -> DeepBookVwapSource {
+        return DeepBookVwapSource{ client, base_asset, quote_asset, price_decimals, max_age_s }
+    }
+}
+
+#[async_trait]
+impl PriceSource for DeepBookVwapSource<'_> {
+    fn name(&self) -> String {
+        return "deepbook-vwap".to_string();
+    }
+
+    async fn get_price(&self) -> Result<ValidatedPrice, OracleError> {
+        let events = try_query_fills(self.client, self.base_asset.clone(), self.quote_asset.clone()).await.ok_or(OracleError::Unavailable)?;
+        let (raw_price, min_price, max_price) = vwap(&events).ok_or(OracleError::Unavailable)?;
+        let price = raw_price / self.price_decimals as f64;
+        let conf = ((max_price - min_price) as f64 / 2.0) / self.price_decimals as f64;
+        // events are sorted descending, first one is the latest fill
+        let publish_time = (events.first().unwrap().timestamp_ms.unwrap_or(0) / 1000) as i64;
+        let age_s = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64 - publish_time;
+        if age_s > self.max_age_s {
+            return Err(OracleError::Stale { age_s, max_age_s: self.max_age_s });
+        }
+        return Ok(ValidatedPrice{ price, conf, ema_price: price, ema_conf: conf, publish_time });
+    }
+}
+
+pub struct WeightedSource<'a> {
+    pub source: Box<dyn PriceSource + 'a>,
+    // weight in median, sources with 0 weight are used only as fallback
+    pub weight: f64,
+}
+
+// weighted median of healthy sources, if there are less than min_sources healthy weighted sources
+// the price is unavailable unless fallback is on, then the first healthy source in order of sources is used
+pub struct PriceAggregator<'a> {
+    pub sources: Vec<WeightedSource<'a>>,
+    pub min_sources: usize,
+    pub fallback: bool,
+}
+
+impl<'a> PriceAggregator<'a> {
+    pub fn new(min_sources: usize) -> PriceAggregator<'a> {
+        return PriceAggregator{ sources: vec![], min_sources, fallback: false }
+    }
+
+    pub fn add(mut self, source: Box<dyn PriceSource + 'a>, weight: f64) -> PriceAggregator<'a> {
+        self.sources.push(WeightedSource{ source, weight });
+        return self;
+    }
+
+    pub fn with_fallback(mut self, fallback: bool) -> PriceAggregator<'a> {
+        self.fallback = fallback;
+        return self;
+    }
+
+    pub async fn get_price(&self) -> Result<ValidatedPrice, OracleError> {
+        return self.get_price_with_source().await.map(|x| x.0);
+    }
+
+    // price and whether its source is the deepbook mid itself
+    pub async fn get_price_with_source(&self) -> Result<(ValidatedPrice, bool), OracleError> {
+        // price, weight and index of source
+        let mut healthy: Vec<(ValidatedPrice, f64, usize)> = vec![];
+        for (i, item) in self.sources.iter().enumerate() {
+            // fallback only sources are not even asked without fallback
+            if item.weight <= 0.0 && !self.fallback {
+                continue;
+            }
+            match item.source.get_price().await {
+                Ok(price) => {
+                    debug!("{}: {:?}", item.source.name(), price);
+                    healthy.push((price, item.weight, i));
+                }
+                Err(err) => warn!("price source {} unhealthy: {:?}", item.source.name(), err),
+            }
+        }
+        let weighted: Vec<(ValidatedPrice, f64, usize)> = healthy.iter().filter(|x| x.1 > 0.0).cloned().collect();
+        if weighted.len() >= self.min_sources.max(1) {
+            let prices = weighted.iter().map(|x| (x.0, x.1)).collect();
+            let index = weighted_median_index(&prices).ok_or(OracleError::Unavailable)?;
+            let (price, _, source) = weighted[index];
+            return Ok((price, self.sources[source].source.is_book_mid()));
+        }
+        if self.fallback && healthy.len() > 0 {
+            let (price, _, source) = healthy[0];
+            warn!("not enough healthy price sources, falling back to {}", self.sources[source].source.name());
+            return Ok((price, self.sources[source].source.is_book_mid()));
+        }
+        warn!("{} of {} weighted price sources healthy, no price", weighted.len(), self.min_sources.max(1));
+        return Err(OracleError::Unavailable);
+    }
+}
+
+// price at which cumulative weight reaches half of total weight
+pub fn weighted_median(prices: &Vec<(ValidatedPrice, f64)>) -> Option<ValidatedPrice> {
+    return weighted_median_index(prices).map(|i| prices[i].0);
+}
+
+// index in prices of the weighted median
+pub fn weighted_median_index(prices: &Vec<(ValidatedPrice, f64)>) -> Option<usize> {
+    let mut sorted: Vec<usize> = (0..prices.len()).collect();
+    sorted.sort_by(|a, b| prices[*a].0.price.partial_cmp(&prices[*b].0.price).unwrap());
+    let total: f64 = prices.iter().map(|x| x.1).sum();
+    let mut cumulative = 0.0;
+    for i in sorted {
+        cumulative += prices[i].1;
+        if cumulative >= total / 2.0 {
+            return Some(i);
+        }
+    }
+    return None;
+}
@@ -0,0 +1,212 @@
+use std::collections::VecDeque;
+use serde_derive::{Deserialize, Serialize};
+use sui_sdk::rpc_types::SuiEvent;
+use sui_types::base_types::ObjectID;
+use crate::constant::FLOAT_SCALING;
+use crate::events::{decode_event, DeepBookEvent, OrderFilledEvent};
+
+pub const MINUTE_MS: u64 = 60_000;
+pub const HOUR_MS: u64 = 3_600_000;
+pub const WINDOWS_MS: [u64; 3] = [MINUTE_MS, 5 * MINUTE_MS, HOUR_MS];
+// quote distances from mid of arrival rates in basis points
+pub const DISTANCES_BPS: [f64; 6] = [0.0, 5.0, 10.0, 20.0, 50.0, 100.0];
+// mid sampled longer before a fill is not used for it
+pub const MAX_MID_AGE_MS: u64 = MINUTE_MS;
+
+// one OrderFilled of the pool
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trade {
+    pub timestamp_ms: u64,
+    // deepbook price units
+    pub price: u64,
+    pub quantity: u64,
+    // taker bought base i.e. the maker order was an ask
+    pub taker_buy: bool,
+    // deepbook mid last sampled before the fill, trades without mid are left out of arrival rates
+    pub mid: Option<f64>,
+}
+
+impl Trade {
+    pub fn new(event: &OrderFilledEvent, timestamp_ms: u64, mid: Option<f64>) -> Trade {
+        return Trade{ timestamp_ms, price: event.price, quantity: event.base_asset_quantity_filled, taker_buy: !event.is_bid, mid }
+    }
+
+    // raw quote units
+    pub fn notional(&self) -> f64 {
+        return self.price as f64 * self.quantity as f64 / FLOAT_SCALING as f64;
+    }
+}
+
+// fills per second which would have reached a quote distance_bps away from mid
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct ArrivalRate {
+    pub distance_bps: f64,
+    // takers selling at or below mid - distance
+    pub bid_per_s: f64,
+    // takers buying at or above mid + distance
+    pub ask_per_s: f64,
+}
+
+// volumes in raw base units, notional in raw quote units, vwap in deepbook price units
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct WindowStats {
+    pub window_ms: u64,
+    pub count: u64,
+    pub volume: f64,
+    pub notional: f64,
+    pub vwap: Option<f64>,
+    pub buy_volume: f64,
+    pub sell_volume: f64,
+    // (buy - sell) / (buy + sell) of taker volume, 0 without trades
+    pub imbalance: f64,
+    pub average_size: f64,
+    pub arrival: Vec<ArrivalRate>,
+}
+
+impl WindowStats {
+    pub fn notional_per_s(&self) -> f64 {
+        return self.notional / seconds(self.window_ms);
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct MarketSnapshot {
+    pub timestamp_ms: u64,
+    pub windows: Vec<WindowStats>,
+}
+
+impl MarketSnapshot {
+    pub fn window(&self, window_ms: u64) -> Option<&WindowStats> {
+        return self.windows.iter().find(|x| x.window_ms == window_ms);
+    }
+}
+
+fn seconds(window_ms: u64) -> f64 {
+    return window_ms.max(1) as f64 / 1000.0;
+}
+
+// fills of one pool over the longest window, stats are taken over windows ending now
+pub struct MarketStats {
+    pub pool_id: ObjectID,
+    windows_ms: Vec<u64>,
+    distances_bps: Vec<f64>,
+    // oldest first
+    trades: VecDeque<Trade>,
+    // (timestamp, mid) samples oldest first, a fill is measured against the book before it and not the one it left behind
+    mids: VecDeque<(u64, f64)>,
+}
+
+impl MarketStats {
+    pub fn new(pool_id: ObjectID, windows_ms: Vec<u64>, distances_bps: Vec<f64>) -> MarketStats {
+        return MarketStats{ pool_id, windows_ms, distances_bps, trades: VecDeque::new(), mids: VecDeque::new() }
+    }
+
+    // 1m, 5m and 1h windows with default distances
+    pub fn with_defaults(pool_id: ObjectID) -> MarketStats {
+        return MarketStats::new(pool_id, WINDOWS_MS.to_vec(), DISTANCES_BPS.to_vec());
+    }
+
+    pub fn horizon_ms(&self) -> u64 {
+        return self.windows_ms.iter().cloned().max().unwrap_or(0);
+    }
+
+    // deepbook mid in price units sampled at now_ms
+    pub fn set_mid(&mut self, now_ms: u64, mid: f64) {
+        let index = self.mids.partition_point(|x| x.0 <= now_ms);
+        self.mids.insert(index, (now_ms, mid));
+    }
+
+    // last mid sampled at or before timestamp_ms, None if there is none within MAX_MID_AGE_MS
+    pub fn mid_at(&self, timestamp_ms: u64) -> Option<f64> {
+        let index = self.mids.partition_point(|x| x.0 <= timestamp_ms);
+        if index == 0 {
+            return None;
+        }
+        let (sampled_ms, mid) = self.mids[index - 1];
+        if timestamp_ms - sampled_ms > MAX_MID_AGE_MS {
+            return None;
+        }
+        return Some(mid);
+    }
+
+    pub fn len(&self) -> usize {
+        return self.trades.len();
+    }
+
+    // backfilled trades may come out of order
+    pub fn push(&mut self, trade: Trade) {
+        let index = self.trades.partition_point(|x| x.timestamp_ms <= trade.timestamp_ms);
+        self.trades.insert(index, trade);
+    }
+
+    // OrderFilled of this pool, true if added
+    pub fn push_event(&mut self, event: &SuiEvent) -> bool {
+        let decoded = decode_event(event);
+        if decoded.is_none() {
+            return false;
+        }
+        return match decoded.unwrap().event {
+            DeepBookEvent::OrderFilled(x) if x.pool_id == self.pool_id => {
+                let timestamp_ms = event.timestamp_ms.unwrap_or(0);
+                self.push(Trade::new(&x, timestamp_ms, self.mid_at(timestamp_ms)));
+                true
+            }
+            _ => false,
+        };
+    }
+
+    pub fn window(&self, now_ms: u64, window_ms: u64) -> WindowStats {
+        let mut stats = WindowStats{ window_ms, ..Default::default() };
+        let mut hits = vec![(0u64, 0u64); self.distances_bps.len()];
+        for trade in self.trades.iter().rev() {
+            if trade.timestamp_ms + window_ms <= now_ms {
+                break;
+            }
+            stats.count += 1;
+            stats.volume += trade.quantity as f64;
+            stats.notional += trade.notional();
+            if trade.taker_buy {
+                stats.buy_volume += trade.quantity as f64;
+            } else {
+                stats.sell_volume += trade.quantity as f64;
+            }
+            if trade.mid.is_none() {
+                continue;
+            }
+            let mid = trade.mid.unwrap();
+            for (i, distance) in self.distances_bps.iter().enumerate() {
+                if trade.taker_buy && trade.price as f64 >= mid * (1.0 + distance / 10_000.0) {
+                    hits[i].1 += 1;
+                } else if !trade.taker_buy && trade.price as f64 <= mid * (1.0 - distance / 10_000.0) {
+                    hits[i].0 += 1;
+                }
+            }
+        }
+        if stats.count > 0 {
+            stats.vwap = Some(stats.notional * FLOAT_SCALING as f64 / stats.volume);
+            stats.average_size = stats.volume / stats.count as f64;
+        }
+        if stats.volume > 0.0 {
+            stats.imbalance = (stats.buy_volume - stats.sell_volume) / stats.volume;
+        }
+        stats.arrival = self.distances_bps.iter().zip(hits.iter()).map(|(distance, (bid, ask))| ArrivalRate{
+            distance_bps: *distance,
+            bid_per_s: *bid as f64 / seconds(window_ms),
+            ask_per_s: *ask as f64 / seconds(window_ms),
+        }).collect();
+        return stats;
+    }
+
+    // drops trades and mid samples older than the longest window, the last mid is kept for fills still to come
+    pub fn snapshot(&mut self, now_ms: u64) -> MarketSnapshot {
+        let horizon_ms = self.horizon_ms();
+        while self.trades.front().is_some() && self.trades.front().unwrap().timestamp_ms + horizon_ms <= now_ms {
+            self.trades.pop_front();
+        }
+        while self.mids.len() > 1 && self.mids.front().unwrap().0 + horizon_ms <= now_ms {
+            self.mids.pop_front();
+        }
+        let windows = self.windows_ms.iter().map(|x| self.window(now_ms, *x)).collect();
+        return MarketSnapshot{ timestamp_ms: now_ms, windows };
+    }
+}
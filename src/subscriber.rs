@@ -0,0 +1,293 @@
+use std::collections::{HashSet, VecDeque};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use futures_util::{SinkExt, StreamExt};
+use log::{debug, info, warn};
+use serde_json::{json, Value};
+use sui_sdk::rpc_types::{EventFilter, SuiEvent};
+use sui_sdk::SuiClient;
+use sui_types::base_types::ObjectID;
+use sui_types::event::EventID;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+use crate::constant::DEEPBOOK_PKG;
+use crate::events::decode_event;
+use crate::market::query_fills_since;
+use crate::stats::MarketStats;
+
+pub const SUBSCRIBE_METHOD: &str = "suix_subscribeEvent";
+pub const RECONNECT_MS: u64 = 1_000;
+// ids of the last published events, backfill and subscription may overlap
+const SEEN_LENGTH: usize = 1_000;
+
+// events missed while the subscription was down
+#[async_trait]
+pub trait EventBackfill: Send + Sync {
+    // events matching filter after cursor, oldest first
+    async fn events_after(&self, filter: &EventFilter, cursor: EventID) -> Result<Vec<SuiEvent>, String>;
+}
+
+// query_events of the http json-rpc
+pub struct QueryBackfill {
+    client: SuiClient,
+}
+
+impl QueryBackfill {
+    pub fn new(client: SuiClient) -> QueryBackfill {
+        return QueryBackfill{ client }
+    }
+}
+
+#[async_trait]
+impl EventBackfill for QueryBackfill {
+    async fn events_after(&self, filter: &EventFilter, cursor: EventID) -> Result<Vec<SuiEvent>, String> {
+        let mut events = vec![];
+        let mut cursor = Some(cursor);
+        loop {
+            let page = self.client.event_api().query_events(filter.clone(), cursor, None, false).await;
+            if page.is_err() {
+                return Err(page.err().unwrap().to_string());
+            }
+            let page = page.unwrap();
+            let done = !page.has_next_page || page.data.len() == 0;
+            if page.data.len() > 0 {
+                cursor = Some(page.data.last().unwrap().id);
+            }
+            events.extend(page.data);
+            if done {
+                break;
+            }
+        }
+        return Ok(events);
+    }
+}
+
+// events of every clob_v2 type, the rpc cannot filter by pool so events of other pools are dropped by the subscriber
+pub fn deepbook_filter() -> EventFilter {
+    return EventFilter::MoveEventModule{ package: ObjectID::from_str(DEEPBOOK_PKG).unwrap(), module: "clob_v2".parse().unwrap() };
+}
+
+pub fn subscribe_request(id: u64, filter: &EventFilter) -> String {
+    return json!({"jsonrpc": "2.0", "id": id, "method": SUBSCRIBE_METHOD, "params": [filter]}).to_string();
+}
+
+#[derive(Debug, Clone)]
+pub enum RpcMessage {
+    // response to subscribe request with subscription id
+    Subscribed(Value),
+    Event(SuiEvent),
+    Error(String),
+    Other,
+}
+
+// one text frame of the websocket json-rpc
+pub fn parse_message(text: &str) -> RpcMessage {
+    let value = serde_json::from_str::<Value>(text);
+    if value.is_err() {
+        return RpcMessage::Error(format!("invalid json: {}", text));
+    }
+    let value = value.unwrap();
+    if value.get("error").is_some() {
+        return RpcMessage::Error(value["error"].to_string());
+    }
+    if value.get("id").is_some() && value.get("result").is_some() {
+        return RpcMessage::Subscribed(value["result"].clone());
+    }
+    if value.get("method").and_then(|x| x.as_str()) == Some(SUBSCRIBE_METHOD) {
+        let event = serde_json::from_value::<SuiEvent>(value["params"]["result"].clone());
+        if event.is_err() {
+            return RpcMessage::Error(format!("invalid event: {:?}", event.err()));
+        }
+        return RpcMessage::Event(event.unwrap());
+    }
+    return RpcMessage::Other;
+}
+
+// deepbook events of pools from one websocket subscription, published in order on a channel,
+// after every reconnect the events after the last published one are backfilled
+pub struct EventSubscriber<B: EventBackfill> {
+    ws_url: String,
+    filter: EventFilter,
+    pool_ids: Vec<ObjectID>,
+    backfill: B,
+    cursor: Option<EventID>,
+    seen: VecDeque<EventID>,
+    reconnect_ms: u64,
+}
+
+impl<B: EventBackfill + 'static> EventSubscriber<B> {
+    // without cursor the first connection starts at live events
+    pub fn new(ws_url: String, pool_ids: Vec<ObjectID>, backfill: B, cursor: Option<EventID>, reconnect_ms: u64) -> EventSubscriber<B> {
+        return EventSubscriber{ ws_url, filter: deepbook_filter(), pool_ids, backfill, cursor, seen: VecDeque::new(), reconnect_ms }
+    }
+
+    pub fn cursor(&self) -> Option<EventID> {
+        return self.cursor;
+    }
+
+    pub fn spawn(self, sender: mpsc::Sender<SuiEvent>) -> JoinHandle<()> {
+        return tokio::spawn(self.run(sender));
+    }
+
+    // until the receiver is dropped
+    pub async fn run(mut self, sender: mpsc::Sender<SuiEvent>) {
+        loop {
+            let result = self.session(&sender).await;
+            if result.is_ok() || sender.is_closed() {
+                return;
+            }
+            warn!("event subscription {} dropped: {:?}, reconnecting from {:?}", self.ws_url, result.err(), self.cursor);
+            sleep(Duration::from_millis(self.reconnect_ms)).await;
+        }
+    }
+
+    // one connection, Ok when the receiver is dropped
+    async fn session(&mut self, sender: &mpsc::Sender<SuiEvent>) -> Result<(), String> {
+        let (mut socket, _) = connect_async(self.ws_url.as_str()).await.map_err(|e| e.to_string())?;
+        socket.send(Message::Text(subscribe_request(1, &self.filter))).await.map_err(|e| e.to_string())?;
+        while let Some(message) = socket.next().await {
+            let text = match message.map_err(|e| e.to_string())? {
+                Message::Text(x) => x,
+                Message::Close(x) => return Err(format!("closed by server: {:?}", x)),
+                _ => continue,
+            };
+            match parse_message(&text) {
+                RpcMessage::Subscribed(id) => {
+                    debug!("subscribed to deepbook events of {:?}, subscription {}", self.pool_ids, id);
+                    // backfill once subscribed so that nothing falls in between, overlap is dropped as seen
+                    if self.cursor.is_some() {
+                        let missed = self.backfill.events_after(&self.filter, self.cursor.unwrap()).await?;
+                        info!("backfilled {} events after {:?}", missed.len(), self.cursor);
+                        for event in missed {
+                            if !self.publish(event, sender).await {
+                                return Ok(());
+                            }
+                        }
+                    }
+                }
+                RpcMessage::Event(event) => {
+                    if !self.publish(event, sender).await {
+                        return Ok(());
+                    }
+                }
+                RpcMessage::Error(e) => return Err(e),
+                RpcMessage::Other => {}
+            }
+        }
+        return Err("connection closed".to_string());
+    }
+
+    // false when the receiver is dropped
+    async fn publish(&mut self, event: SuiEvent, sender: &mpsc::Sender<SuiEvent>) -> bool {
+        if self.seen.contains(&event.id) {
+            return true;
+        }
+        let decoded = decode_event(&event);
+        if decoded.is_none() || !self.pool_ids.contains(&decoded.unwrap().event.pool_id()) {
+            return true;
+        }
+        self.seen.push_back(event.id);
+        if self.seen.len() > SEEN_LENGTH {
+            self.seen.pop_front();
+        }
+        self.cursor = Some(event.id);
+        return sender.send(event).await.is_ok();
+    }
+}
+
+// loads fills of the longest window of stats, ids of loaded events most recent first
+async fn seed_stats(client: &SuiClient, base_asset: &str, quote_asset: &str, stats: &Arc<Mutex<MarketStats>>) -> Vec<EventID> {
+    let (pool_id, horizon_ms) = {
+        let guard = stats.lock().await;
+        (guard.pool_id, guard.horizon_ms())
+    };
+    let t = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+    let history = query_fills_since(client, pool_id, base_asset.to_string(), quote_asset.to_string(), t.saturating_sub(horizon_ms)).await;
+    let mut guard = stats.lock().await;
+    for event in history.iter().rev() {
+        guard.push_event(event);
+    }
+    info!("loaded {} fills of pool {}", guard.len(), pool_id);
+    return history.iter().map(|x| x.id).collect();
+}
+
+// OrderFilled of one pool after cursor every poll_sec
+async fn poll_stats(client: SuiClient, base_asset: String, quote_asset: String, stats: Arc<Mutex<MarketStats>>, poll_sec: u64, mut cursor: Option<EventID>) {
+    let pool_id = stats.lock().await.pool_id;
+    let backfill = QueryBackfill::new(client.clone());
+    let filter = EventFilter::MoveEventType(format!("0xdee9::clob_v2::OrderFilled<{base_asset}, {quote_asset}>").parse().unwrap());
+    loop {
+        sleep(Duration::from_secs(poll_sec)).await;
+        if cursor.is_none() {
+            let latest = client.event_api().query_events(filter.clone(), None, Some(1), true).await;
+            if latest.is_ok() {
+                cursor = latest.unwrap().data.first().map(|x| x.id);
+            }
+            continue;
+        }
+        let events = backfill.events_after(&filter, cursor.unwrap()).await;
+        if events.is_err() {
+            warn!("could not poll fills of pool {}: {:?}", pool_id, events.err());
+            continue;
+        }
+        let events = events.unwrap();
+        let mut guard = stats.lock().await;
+        for event in events.iter() {
+            guard.push_event(event);
+        }
+        if events.len() > 0 {
+            cursor = Some(events.last().unwrap().id);
+        }
+    }
+}
+
+// keeps fills of the pool in stats, see feed_pools_stats
+pub async fn feed_stats(client: SuiClient, ws_url: Option<String>, base_asset: String, quote_asset: String, stats: Arc<Mutex<MarketStats>>, poll_sec: u64) {
+    feed_pools_stats(client, ws_url, vec![(base_asset, quote_asset)], vec![stats], poll_sec).await;
+}
+
+// keeps fills of every pool in its stats, history of the longest window is loaded first,
+// with ws_url one subscription carries events of all pools and they are fanned out by pool_id, otherwise every pool is polled every poll_sec
+pub async fn feed_pools_stats(client: SuiClient, ws_url: Option<String>, assets: Vec<(String, String)>, stats: Vec<Arc<Mutex<MarketStats>>>, poll_sec: u64) {
+    if ws_url.is_none() {
+        let mut tasks = vec![];
+        for (i, (base_asset, quote_asset)) in assets.into_iter().enumerate() {
+            let seeded = seed_stats(&client, &base_asset, &quote_asset, &stats[i]).await;
+            tasks.push(tokio::spawn(poll_stats(client.clone(), base_asset, quote_asset, Arc::clone(&stats[i]), poll_sec, seeded.first().cloned())));
+        }
+        join_all(tasks).await;
+        return;
+    }
+    let mut pool_ids = vec![];
+    for item in stats.iter() {
+        pool_ids.push(item.lock().await.pool_id);
+    }
+    // subscription starts after the latest deepbook event, history loaded afterwards covers everything before it
+    let latest = client.event_api().query_events(deepbook_filter(), None, Some(1), true).await;
+    if latest.is_err() {
+        warn!("could not fetch latest deepbook event, starting at live events: {:?}", latest);
+    }
+    let cursor = latest.ok().and_then(|x| x.data.first().map(|e| e.id));
+    let (sender, mut events) = mpsc::channel(SEEN_LENGTH);
+    EventSubscriber::new(ws_url.unwrap(), pool_ids.clone(), QueryBackfill::new(client.clone()), cursor, RECONNECT_MS).spawn(sender);
+    // events after the cursor can also be in the history
+    let mut seeded = HashSet::new();
+    for (i, (base_asset, quote_asset)) in assets.iter().enumerate() {
+        seeded.extend(seed_stats(&client, base_asset, quote_asset, &stats[i]).await);
+    }
+    while let Some(event) = events.recv().await {
+        if seeded.contains(&event.id) {
+            continue;
+        }
+        let index = decode_event(&event).and_then(|x| pool_ids.iter().position(|id| *id == x.event.pool_id()));
+        if index.is_some() {
+            stats[index.unwrap()].lock().await.push_event(&event);
+        }
+    }
+}
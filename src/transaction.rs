@@ -1,31 +1,281 @@
+use std::collections::HashMap;
 use std::env;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use log::{debug, warn};
-use shared_crypto::intent::Intent;
-use sui_keys::keystore::{AccountKeystore, InMemKeystore};
-use sui_sdk::rpc_types::SuiTransactionBlockResponseOptions;
+use sui_sdk::rpc_types::{SuiExecutionStatus, SuiObjectDataOptions, SuiTransactionBlockEffects, SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions};
 use sui_sdk::SuiClient;
-use sui_types::base_types::{ObjectRef, SuiAddress};
+use sui_types::base_types::{ObjectID, ObjectRef, SuiAddress};
 use sui_types::crypto::SignatureScheme;
+use sui_types::gas::GasCostSummary;
 use sui_types::digests::{Digest, TransactionDigest};
 use sui_types::quorum_driver_types::ExecuteTransactionRequestType;
 use sui_types::transaction::{ProgrammableTransaction, Transaction, TransactionData};
+use tokio::sync::Mutex;
+use tokio::time::{sleep, timeout};
+use crate::gas::GasPool;
+use crate::signer::{KeystoreSigner, Signer};
+
+// (objects with new versions, objects deleted or wrapped) by the transaction
+pub fn refs_from_effects(effects: &SuiTransactionBlockEffects) -> (Vec<ObjectRef>, Vec<ObjectID>) {
+    let updated = effects.created().iter()
+        .chain(effects.mutated().iter())
+        .chain(effects.unwrapped().iter())
+        .map(|x| x.reference.to_object_ref())
+        .collect();
+    let removed = effects.deleted().iter()
+        .chain(effects.wrapped().iter())
+        .chain(effects.unwrapped_then_deleted().iter())
+        .map(|x| x.object_id)
+        .collect();
+    return (updated, removed);
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SubmitConfig {
+    // retries after the first attempt, only for transport errors and timeouts
+    pub retries: u32,
+    // doubled after every retry
+    pub backoff_ms: u64,
+    // timeout of one attempt
+    pub timeout_ms: u64,
+}
+
+impl Default for SubmitConfig {
+    fn default() -> Self {
+        return SubmitConfig{ retries: 3, backoff_ms: 200, timeout_ms: 10_000 }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GasConfig {
+    // dry run gas cost is multiplied by margin
+    pub budget_margin: f64,
+    pub min_budget: u64,
+    // cap of estimated budget, also used for dry run
+    pub max_budget: u64,
+    // reference gas price multiplier of urgent transactions
+    pub tip_multiplier: f64,
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        return GasConfig{ budget_margin: 1.2, min_budget: 2_000_000, max_budget: 50_000_000, tip_multiplier: 1.0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct EpochGasPrice {
+    epoch: u64,
+    price: u64,
+    epoch_end_ms: u64,
+}
+
+// computation and storage cost with margin, rebate is paid only after execution so it is not subtracted
+pub fn estimate_budget(summary: &GasCostSummary, config: &GasConfig) -> u64 {
+    let cost = summary.computation_cost + summary.storage_cost;
+    let budget = (cost as f64 * config.budget_margin) as u64;
+    return budget.max(config.min_budget).min(config.max_budget);
+}
+
+#[derive(Debug)]
+pub enum SubmitResult {
+    Success(Box<SuiTransactionBlockResponse>),
+    // executed, aborted in move code i.e. post or abort order crossing the book
+    MoveAbort { response: Box<SuiTransactionBlockResponse>, abort: MoveAbort },
+    // executed, failed for other reason i.e. insufficient gas
+    Failed { response: Box<SuiTransactionBlockResponse>, error: String },
+    // rejected before execution for a reason retrying does not fix i.e. object version conflict,
+    // insufficient gas or invalid signature, not retried
+    Rejected(String),
+    // rpc error after all retries, transaction may or may not be executed
+    Transport(String),
+    Timeout,
+    NotSigned,
+    NoGas,
+}
+
+impl SubmitResult {
+    pub fn is_success(&self) -> bool {
+        return matches!(self, SubmitResult::Success(_));
+    }
+
+    pub fn response_ref(&self) -> Option<&SuiTransactionBlockResponse> {
+        match self {
+            SubmitResult::Success(response) => Some(response.as_ref()),
+            SubmitResult::MoveAbort { response, .. } => Some(response.as_ref()),
+            SubmitResult::Failed { response, .. } => Some(response.as_ref()),
+            _ => None,
+        }
+    }
+
+    pub fn response(self) -> Option<SuiTransactionBlockResponse> {
+        match self {
+            SubmitResult::Success(response) => Some(*response),
+            SubmitResult::MoveAbort { response, .. } => Some(*response),
+            SubmitResult::Failed { response, .. } => Some(*response),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveAbort {
+    pub module: String,
+    pub code: u64,
+    // error constant name for deepbook modules
+    pub name: Option<String>,
+}
+
+// error constants of deepbook clob_v2
+pub fn clob_v2_error_name(code: u64) -> Option<&'static str> {
+    match code {
+        1 => Some("EIncorrectPoolOwner"),
+        2 => Some("EInvalidFeeRateRebateRate"),
+        3 => Some("EInvalidOrderId"),
+        4 => Some("EUnauthorizedCancel"),
+        5 => Some("EInvalidPrice"),
+        6 => Some("EInvalidQuantity"),
+        7 => Some("EInsufficientBaseCoin"),
+        8 => Some("EInsufficientQuoteCoin"),
+        9 => Some("EOrderCannotBeFullyFilled"),
+        10 => Some("EOrderCannotBeFullyPassive"),
+        11 => Some("EInvalidTickPrice"),
+        12 => Some("EInvalidUser"),
+        13 => Some("ENotEqual"),
+        14 => Some("EInvalidRestriction"),
+        16 => Some("EInvalidPair"),
+        18 => Some("EInvalidFee"),
+        19 => Some("EInvalidExpireTimestamp"),
+        20 => Some("EInvalidTickSizeLotSize"),
+        21 => Some("EInvalidSelfMatchingPreventionArg"),
+        _ => None,
+    }
+}
+
+/*
+failure status of a move abort looks like
+MoveAbort(MoveLocation { module: ModuleId { address: dee9, name: Identifier("clob_v2") }, function: 33, instruction: 39, function_name: Some("place_limit_order_int") }, 10) in command 0
+ */
+pub fn parse_move_abort(error: &str) -> Option<MoveAbort> {
+    if !error.starts_with("MoveAbort") {
+        return None;
+    }
+    let name_start = error.find("Identifier(\"")? + "Identifier(\"".len();
+    let module = error[name_start..].split('"').next()?.to_string();
+    let code_start = error.rfind("}, ")? + "}, ".len();
+    let code = error[code_start..].split(')').next()?.trim().parse::<u64>().ok()?;
+    let name = if module == "clob_v2" {clob_v2_error_name(code).map(|x| x.to_string())} else {None};
+    return Some(MoveAbort{ module, code, name });
+}
+
+// parts of validator and rpc error messages which are the same on every attempt
+const DETERMINISTIC_ERRORS: [&str; 12] = [
+    "ObjectVersionUnavailableForConsumption",
+    "not available for consumption",
+    "ObjectNotFound",
+    "InsufficientGas",
+    "GasBalanceTooLow",
+    "GasBudgetTooLow",
+    "GasBudgetTooHigh",
+    "InvalidSignature",
+    "Invalid user signature",
+    "SignerSignatureAbsent",
+    "ObjectLockConflict",
+    "issues with transaction inputs",
+];
+
+// network errors and unknown errors are retried, errors caused by the transaction itself are not
+pub fn is_deterministic_error(error: &str) -> bool {
+    return DETERMINISTIC_ERRORS.iter().any(|x| error.contains(x));
+}
+
+pub fn classify(response: SuiTransactionBlockResponse) -> SubmitResult {
+    let status = response.effects.as_ref().map(|x| x.status().clone());
+    match status {
+        Some(SuiExecutionStatus::Failure { error }) => {
+            let abort = parse_move_abort(&error);
+            if abort.is_some() {
+                warn!("transaction {} aborted: {:?}", response.digest, abort);
+                return SubmitResult::MoveAbort{ response: Box::new(response), abort: abort.unwrap() };
+            }
+            warn!("transaction {} failed: {}", response.digest, error);
+            return SubmitResult::Failed{ response: Box::new(response), error };
+        }
+        _ => return SubmitResult::Success(Box::new(response)),
+    }
+}
 
 pub struct TransactionWrapper<'a> {
     client: &'a SuiClient,
-    keystore: InMemKeystore,
+    key_signer: Box<dyn Signer>,
     pub signer: SuiAddress,
+    // latest known refs of owned objects, updated from effects of executed transactions
+    object_refs: Mutex<HashMap<ObjectID, ObjectRef>>,
+    // gas coin used when none is provided
+    gas_coin: Mutex<Option<ObjectID>>,
+    pub submit_config: SubmitConfig,
+    pub gas_config: GasConfig,
+    gas_price: Mutex<Option<EpochGasPrice>>,
 }
 
 impl TransactionWrapper<'_> {
 
+    // ED25519 key from mnemonic in $SUI_WALLET
     pub fn new(client: &SuiClient) -> TransactionWrapper {
-        let mut keystore = InMemKeystore::default();
         let mnemonic = env::var("SUI_WALLET").expect("$SUI_WALLET is not set");
-        keystore.import_from_mnemonic(&*mnemonic,
-                                      SignatureScheme::ED25519, None
-        );
-        let sender = keystore.addresses().first().unwrap().clone();
-        return TransactionWrapper{ client, keystore: keystore, signer: sender }
+        return TransactionWrapper::with_signer(client, Box::new(KeystoreSigner::from_mnemonic(&*mnemonic, SignatureScheme::ED25519, None)));
+    }
+
+    pub fn with_signer(client: &SuiClient, key_signer: Box<dyn Signer>) -> TransactionWrapper {
+        let sender = key_signer.address();
+        return TransactionWrapper{ client, key_signer, signer: sender, object_refs: Mutex::new(HashMap::new()), gas_coin: Mutex::new(None), submit_config: SubmitConfig::default(), gas_config: GasConfig::default(), gas_price: Mutex::new(None) }
+    }
+
+    // ref from cache, fetched only when the object was not seen yet
+    pub async fn object_ref(&self, object_id: ObjectID) -> ObjectRef {
+        let cached = self.object_refs.lock().await.get(&object_id).cloned();
+        if cached.is_some() {
+            return cached.unwrap();
+        }
+        let response = self.client.read_api().get_object_with_options(object_id, SuiObjectDataOptions::new()).await.unwrap();
+        let object_ref = response.object().unwrap().object_ref();
+        self.object_refs.lock().await.insert(object_id, object_ref);
+        return object_ref;
+    }
+
+    // last used gas coin from cache, otherwise first coin with more than 0.5 SUI
+    async fn default_gas_coin(&self) -> ObjectRef {
+        let gas_coin = self.gas_coin.lock().await.clone();
+        if gas_coin.is_some() {
+            let cached = self.object_refs.lock().await.get(&gas_coin.unwrap()).cloned();
+            if cached.is_some() {
+                return cached.unwrap();
+            }
+        }
+        let coin = self.client
+            .coin_read_api()
+            .get_coins(self.signer, None, None, None)
+            .await.unwrap().data.into_iter().filter(|x| x.balance > 10 * 50_000_000).next().unwrap().object_ref();
+        *self.gas_coin.lock().await = Some(coin.0);
+        return coin;
+    }
+
+    pub async fn invalidate(&self, object_id: &ObjectID) {
+        self.object_refs.lock().await.remove(object_id);
+    }
+
+    async fn update_object_refs(&self, effects: &SuiTransactionBlockEffects) {
+        let (updated, removed) = refs_from_effects(effects);
+        let mut object_refs = self.object_refs.lock().await;
+        for object_ref in updated.iter() {
+            // only objects which are already tracked, created objects may not be owned by signer
+            if object_refs.contains_key(&object_ref.0) {
+                object_refs.insert(object_ref.0, *object_ref);
+            }
+        }
+        for object_id in removed.iter() {
+            object_refs.remove(object_id);
+        }
     }
 
     pub async fn process_ptx(&self, ptx: ProgrammableTransaction,
@@ -33,36 +283,158 @@ impl TransactionWrapper<'_> {
                              gasprice: Option<u64>,
                              gasbudget: Option<u64>,
     ) -> Option<TransactionDigest> {
+        let response = self.execute_ptx(ptx, gascoin_object_ref, gasprice, gasbudget).await;
+        return response.map(|x| x.digest);
+    }
+
+    // gas coin is reserved in gas pool for the time of execution, so several transactions can be in flight
+    pub async fn process_ptx_with_pool(&self, ptx: ProgrammableTransaction,
+                                       gas_pool: &GasPool<'_>,
+                                       gasbudget: Option<u64>,
+    ) -> Option<TransactionDigest> {
+        return self.submit_ptx_with_pool(ptx, gas_pool, None, gasbudget).await.response().map(|x| x.digest);
+    }
+
+    // cached until the end of the epoch, reference gas price changes only between epochs
+    pub async fn reference_gas_price(&self) -> u64 {
+        let t = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+        let cached = self.gas_price.lock().await.clone();
+        if cached.is_some() && t < cached.unwrap().epoch_end_ms {
+            return cached.unwrap().price;
+        }
+        let state = self.client.governance_api().get_latest_sui_system_state().await;
+        if state.is_err() {
+            warn!("could not fetch system state: {:?}", state);
+            return self.client.read_api().get_reference_gas_price().await.unwrap();
+        }
+        let state = state.unwrap();
+        let epoch_price = EpochGasPrice{
+            epoch: state.epoch,
+            price: state.reference_gas_price,
+            epoch_end_ms: state.epoch_start_timestamp_ms + state.epoch_duration_ms,
+        };
+        debug!("reference gas price {} in epoch {}", epoch_price.price, epoch_price.epoch);
+        *self.gas_price.lock().await = Some(epoch_price);
+        return epoch_price.price;
+    }
+
+    // gas price with tip for transactions which should be included first i.e. cancels
+    pub async fn urgent_gas_price(&self) -> u64 {
+        return (self.reference_gas_price().await as f64 * self.gas_config.tip_multiplier) as u64;
+    }
+
+    // dry run with max_budget, falls back to max_budget if dry run fails
+    pub async fn estimate_budget(&self, ptx: &ProgrammableTransaction, gas: ObjectRef, gasprice: u64) -> u64 {
+        let tx_data = TransactionData::new_programmable(
+            self.signer,
+            vec![gas],
+            ptx.clone(),
+            self.gas_config.max_budget,
+            gasprice,
+        );
+        let response = self.client.read_api().dry_run_transaction_block(tx_data).await;
+        if response.is_err() {
+            warn!("dry run failed: {:?}", response);
+            return self.gas_config.max_budget;
+        }
+        let effects = response.unwrap().effects;
+        if matches!(effects.status(), SuiExecutionStatus::Failure { .. }) {
+            warn!("dry run status: {:?}", effects.status());
+        }
+        let budget = estimate_budget(effects.gas_cost_summary(), &self.gas_config);
+        debug!("gas budget: {} {:?}", budget, effects.gas_cost_summary());
+        return budget;
+    }
+
+    // full response when the transaction was executed, also when effects are a failure
+    pub async fn execute_ptx(&self, ptx: ProgrammableTransaction,
+                             gascoin_object_ref: Option<ObjectRef>,
+                             gasprice: Option<u64>,
+                             gasbudget: Option<u64>,
+    ) -> Option<SuiTransactionBlockResponse> {
+        return self.submit_ptx(ptx, gascoin_object_ref, gasprice, gasbudget).await.response();
+    }
+
+    // gasbudget estimated by dry run if not provided, the reserved coin has to cover max_budget then
+    pub async fn submit_ptx_with_pool(&self, ptx: ProgrammableTransaction,
+                                      gas_pool: &GasPool<'_>,
+                                      gasprice: Option<u64>,
+                                      gasbudget: Option<u64>,
+    ) -> SubmitResult {
+        let budget = if gasbudget.is_some() {gasbudget.unwrap()} else {self.gas_config.max_budget};
+        let gas = gas_pool.reserve(budget).await;
+        if gas.is_none() {
+            return SubmitResult::NoGas;
+        }
+        let gas = gas.unwrap();
+        let result = self.submit_ptx(ptx, Some(gas), gasprice, gasbudget).await;
+        gas_pool.release(gas.0, result.response_ref()).await;
+        return result;
+    }
+
+    // signs once and retries execution on transport errors and timeouts, executing the same signed transaction twice is safe,
+    // transactions rejected for a deterministic reason are returned immediately
+    pub async fn submit_ptx(&self, ptx: ProgrammableTransaction,
+                            gascoin_object_ref: Option<ObjectRef>,
+                            gasprice: Option<u64>,
+                            gasbudget: Option<u64>,
+    ) -> SubmitResult {
+        let gas = if gascoin_object_ref.is_some() {gascoin_object_ref.unwrap()} else {self.default_gas_coin().await};
+        self.object_refs.lock().await.insert(gas.0, gas);
+        let price = if gasprice.is_some() {gasprice.unwrap()} else {self.reference_gas_price().await};
+        let budget = if gasbudget.is_some() {gasbudget.unwrap()} else {self.estimate_budget(&ptx, gas, price).await};
         let tx_data = TransactionData::new_programmable(
             self.signer,
-            vec![if gascoin_object_ref.is_some() {gascoin_object_ref.unwrap()} else { self.client
-                .coin_read_api()
-                .get_coins(self.signer, None, None, None)
-                .await.unwrap().data.into_iter().filter(|x| x.balance > 10 * 50_000_000).next().unwrap().object_ref() }],
+            vec![gas],
             ptx,
-            if gasbudget.is_some() {gasbudget.unwrap()} else {50_000_000},
-            if gasprice.is_some() {gasprice.unwrap()} else {self.client.read_api().get_reference_gas_price().await.unwrap()},
+            budget,
+            price,
         );
-        // let tx_data = plo;
-        let signature = self.keystore.sign_secure(&self.signer,
-                                             &tx_data,
-                                             Intent::sui_transaction()).unwrap();
+        let signature = self.key_signer.sign(&tx_data).await;
+        if signature.is_none() {
+            return SubmitResult::NotSigned;
+        }
+        let signature = signature.unwrap();
         let tx = Transaction::from_data(tx_data,
                                         vec![signature],
         );
-        let response = self.client.quorum_driver_api().execute_transaction_block(
-            tx,
-            SuiTransactionBlockResponseOptions::full_content(),
-            Some(ExecuteTransactionRequestType::WaitForLocalExecution),
-
-        ).await;
-        if response.is_err() {
-            warn!("got error:{:?}", response);
-        } else {
-            let digest = response.unwrap().digest;
-            debug!("{:?}", digest);
-            return Some(digest);
+        let config = self.submit_config;
+        let mut result = SubmitResult::Timeout;
+        for attempt in 0..=config.retries {
+            if attempt > 0 {
+                sleep(Duration::from_millis(config.backoff_ms * 2_u64.pow(attempt - 1))).await;
+            }
+            let response = timeout(Duration::from_millis(config.timeout_ms), self.client.quorum_driver_api().execute_transaction_block(
+                tx.clone(),
+                SuiTransactionBlockResponseOptions::full_content(),
+                Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+            )).await;
+            match response {
+                Ok(Ok(response)) => {
+                    debug!("{:?}", response.digest);
+                    if response.effects.is_some() {
+                        self.update_object_refs(response.effects.as_ref().unwrap()).await;
+                    }
+                    return classify(response);
+                }
+                Ok(Err(err)) if is_deterministic_error(&err.to_string()) => {
+                    warn!("transaction rejected: {:?}", err);
+                    // cached versions may be the reason
+                    self.object_refs.lock().await.clear();
+                    return SubmitResult::Rejected(err.to_string());
+                }
+                Ok(Err(err)) => {
+                    warn!("attempt {} got error:{:?}", attempt, err);
+                    result = SubmitResult::Transport(err.to_string());
+                }
+                Err(_) => {
+                    warn!("attempt {} timed out after {} ms", attempt, config.timeout_ms);
+                    result = SubmitResult::Timeout;
+                }
+            }
         }
-        return None;
+        // transaction may or may not be executed, versions of used objects are unknown
+        self.object_refs.lock().await.clear();
+        return result;
     }
-}
\ No newline at end of file
+}
@@ -1,9 +1,11 @@
+use log::warn;
 use sui_sdk::error::SuiRpcResult;
-use sui_sdk::rpc_types::{ObjectsPage, SuiExecutionResult, SuiObjectDataFilter, SuiObjectResponseQuery};
+use std::str::FromStr;
+use sui_sdk::rpc_types::{ObjectsPage, SuiExecutionResult, SuiObjectDataFilter, SuiObjectDataOptions, SuiObjectResponseQuery, SuiParsedData};
 use sui_sdk::SuiClient;
-use sui_types::base_types::{ObjectID, SequenceNumber, SuiAddress};
+use sui_types::base_types::{ObjectID, ObjectRef, SequenceNumber, SuiAddress};
 use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
-use sui_types::transaction::{Argument, CallArg, ObjectArg, ProgrammableTransaction};
+use sui_types::transaction::{Argument, CallArg, Command, ObjectArg, ProgrammableTransaction};
 use sui_types::TypeTag;
 use crate::constant::DEEPBOOK_PKG;
 
@@ -18,6 +20,42 @@ pub async fn get_account_cap(client: &SuiClient, address: &SuiAddress) -> SuiRpc
     return response
 }
 
+// owner field of AccountCap, the cap id for a master cap and the owner of the master cap for a child cap,
+// OrderFilled events carry it as maker or taker address
+pub async fn get_account_cap_owner(client: &SuiClient, account_cap: ObjectID) -> Option<SuiAddress> {
+    let response = client.read_api().get_object_with_options(account_cap, SuiObjectDataOptions::new().with_content()).await;
+    if response.is_err() {
+        warn!("could not fetch account cap {}: {:?}", account_cap, response);
+        return None;
+    }
+    match response.unwrap().data?.content? {
+        SuiParsedData::MoveObject(object) => {
+            let json = object.fields.to_json_value();
+            return SuiAddress::from_str(json.get("owner")?.as_str()?).ok();
+        }
+        _ => return None,
+    }
+}
+
+// all AccountCaps of address, follows the cursor until the last page
+pub async fn get_all_account_caps(client: &SuiClient, address: &SuiAddress) -> Vec<ObjectID> {
+    let mut caps = vec![];
+    let mut cursor = None;
+    loop {
+        let page = client.read_api().get_owned_objects(*address, Some(SuiObjectResponseQuery {
+            filter: Some(SuiObjectDataFilter::StructType(
+                ACCOUNT_CAP_TAG.parse().unwrap())),
+            options: None
+        }), cursor, None).await.unwrap();
+        caps.extend(page.data.iter().filter_map(|x| x.data.as_ref().map(|d| d.object_id)));
+        if !page.has_next_page || page.next_cursor.is_none() {
+            break;
+        }
+        cursor = page.next_cursor;
+    }
+    return caps;
+}
+
 // (base_avail, base_locked, quote_avail, quote_locked)
 pub fn parse_result_account_balance(sui_execution_result: &SuiExecutionResult) -> Vec<u64>{
     let mut results = vec![];
@@ -60,4 +98,77 @@ pub fn create_account(owner: SuiAddress) -> ProgrammableTransaction {
     tb.programmable_move_call(DEEPBOOK_PKG.parse().unwrap(), "clob_v2".parse().unwrap(), "create_account".parse().unwrap(), vec![], vec![]);
     tb.transfer_arg(owner, Argument::Result(0));
     return tb.finish();
-}
\ No newline at end of file
+}
+
+// child cap acts on the same custodian balances as admin cap, only admin cap can create it
+pub fn create_child_account(admin_account_cap: ObjectRef, owner: SuiAddress) -> ProgrammableTransaction {
+    let mut tb = ProgrammableTransactionBuilder::new();
+    let admin = tb.obj(ObjectArg::ImmOrOwnedObject(admin_account_cap)).unwrap();
+    let child = tb.programmable_move_call(DEEPBOOK_PKG.parse().unwrap(), "custodian_v2".parse().unwrap(), "create_child_account_cap".parse().unwrap(), vec![], vec![admin]);
+    tb.transfer_arg(owner, child);
+    return tb.finish();
+}
+
+// first coin of coin_type in wallet of owner with at least amount, coins are not merged
+pub async fn select_coin(client: &SuiClient, owner: SuiAddress, coin_type: &str, amount: u64) -> Option<ObjectRef> {
+    let mut cursor = None;
+    loop {
+        let page = client.coin_read_api().get_coins(owner, Some(coin_type.to_string()), cursor, None).await;
+        if page.is_err() {
+            warn!("could not fetch coins of {}: {:?}", coin_type, page);
+            return None;
+        }
+        let page = page.unwrap();
+        let coin = page.data.iter().find(|x| x.balance >= amount);
+        if coin.is_some() {
+            return Some(coin.unwrap().object_ref());
+        }
+        if !page.has_next_page || page.next_cursor.is_none() {
+            return None;
+        }
+        cursor = page.next_cursor;
+    }
+}
+
+// deposits quantity of base or quote asset to custodian, quantity is split from coin,
+// if coin is not provided it is split from gas coin, which works only for SUI
+pub fn deposit(mut tb: ProgrammableTransactionBuilder, baseAsset: TypeTag, quoteAsset: TypeTag, pool_id: ObjectID, pool_sequence_number: SequenceNumber,
+               coin: Option<ObjectRef>, quantity: u64, is_base: bool, account_cap: ObjectRef) -> ProgrammableTransactionBuilder {
+    let source = if coin.is_some() {tb.obj(ObjectArg::ImmOrOwnedObject(coin.unwrap())).unwrap()} else {Argument::GasCoin};
+    let amount = tb.pure(quantity).unwrap();
+    let split = match tb.command(Command::SplitCoins(source, vec![amount])) {
+        Argument::Result(i) => Argument::NestedResult(i, 0),
+        other => other,
+    };
+    let pool = tb.obj(ObjectArg::SharedObject {
+        id: pool_id,
+        initial_shared_version: pool_sequence_number,
+        mutable: true,
+    }).unwrap();
+    let account_cap = tb.obj(ObjectArg::ImmOrOwnedObject(account_cap)).unwrap();
+    tb.programmable_move_call(DEEPBOOK_PKG.parse().unwrap(),
+                              "clob_v2".parse().unwrap(),
+                              (if is_base {"deposit_base"} else {"deposit_quote"}).parse().unwrap(),
+                              vec![baseAsset, quoteAsset],
+                              vec![pool, split, account_cap]);
+    return tb;
+}
+
+// withdraws quantity of base or quote asset from custodian and transfers it to recipient
+pub fn withdraw(mut tb: ProgrammableTransactionBuilder, baseAsset: TypeTag, quoteAsset: TypeTag, pool_id: ObjectID, pool_sequence_number: SequenceNumber,
+                quantity: u64, is_base: bool, account_cap: ObjectRef, recipient: SuiAddress) -> ProgrammableTransactionBuilder {
+    let pool = tb.obj(ObjectArg::SharedObject {
+        id: pool_id,
+        initial_shared_version: pool_sequence_number,
+        mutable: true,
+    }).unwrap();
+    let amount = tb.pure(quantity).unwrap();
+    let account_cap = tb.obj(ObjectArg::ImmOrOwnedObject(account_cap)).unwrap();
+    let coin = tb.programmable_move_call(DEEPBOOK_PKG.parse().unwrap(),
+                                         "clob_v2".parse().unwrap(),
+                                         (if is_base {"withdraw_base"} else {"withdraw_quote"}).parse().unwrap(),
+                                         vec![baseAsset, quoteAsset],
+                                         vec![pool, amount, account_cap]);
+    tb.transfer_arg(recipient, coin);
+    return tb;
+}
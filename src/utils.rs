@@ -24,4 +24,17 @@ pub fn parse_result_u64_from_vec(sui_execution_result: &SuiExecutionResult, offs
         }
     }
     return results;
-}
\ No newline at end of file
+}
+
+// return values of Option<u64>, none is returned as None
+pub fn parse_result_option_u64(sui_execution_result: &SuiExecutionResult) -> Vec<Option<u64>>{
+    let mut results = vec![];
+    for (bytes, _) in sui_execution_result.return_values.iter() {
+        if bytes.len() == 9 && bytes[0] == 1 {
+            results.push(Some(u64::from_le_bytes(bytes[1..9].try_into().unwrap())));
+        } else {
+            results.push(None);
+        }
+    }
+    return results;
+}
@@ -1,35 +1,257 @@
+use std::collections::VecDeque;
 use statistical::standard_deviation;
 use statistical::mean;
 
+pub const YEAR_MS: f64 = 365.25 * 86_400_000.0;
+
+// relative dispersion of the last length prices, spreads and limits of quoting are tuned to it
 #[derive(Debug, Clone)]
 pub struct Volatility {
-    pub prices: Vec<f64>,
+    // the recent value is first value
+    pub prices: VecDeque<f64>,
     pub length: usize,
 }
 
 impl Volatility {
-    // the recent value is first value
-    pub fn insert(&mut self,  value: f64){
-            if self.prices.len() > 0 {
-                self.prices.insert(0, value);
-            } else {
-                self.prices.push(value);
-            }
-            if self.prices.len() > self.length {
-                self.prices.truncate(self.length);
-            }
+    pub fn new(length: usize) -> Volatility {
+        return Volatility{ prices: VecDeque::with_capacity(length + 1), length }
     }
 
-    pub fn volatility(self) -> Option<f64> {
+    pub fn insert(&mut self, value: f64) {
+        self.prices.push_front(value);
+        self.prices.truncate(self.length);
+    }
+
+    pub fn volatility(&self) -> Option<f64> {
         if self.prices.len() == self.length {
-            let mean_val = mean(&self.prices);
-            return Some((standard_deviation((&self.prices), None)/mean_val) * (self.length as f64).sqrt());
+            let prices: Vec<f64> = self.prices.iter().cloned().collect();
+            let mean_val = mean(&prices);
+            return Some((standard_deviation(&prices, None)/mean_val) * (self.length as f64).sqrt());
         } else {
             return None;
         }
     }
 
-    pub fn is_ok(self) -> bool {
+    pub fn is_ok(&self) -> bool {
         return self.prices.len() == self.length;
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceSample {
+    pub timestamp_ms: u64,
+    pub price: f64,
+}
+
+// open, high, low and close of a bar lasting duration_ms
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OhlcBar {
+    pub start_ms: u64,
+    pub duration_ms: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+// estimates variance of log price per millisecond so that irregular sampling is accounted for
+pub trait VolatilityEstimator {
+    type Sample;
+
+    // samples come in time order
+    fn update(&mut self, sample: Self::Sample);
+
+    // None until enough samples
+    fn variance_per_ms(&self) -> Option<f64>;
+
+    // standard deviation of log price change over horizon_ms
+    fn volatility_over(&self, horizon_ms: f64) -> Option<f64> {
+        return self.variance_per_ms().map(|x| (x * horizon_ms).sqrt());
+    }
+
+    fn annualized(&self) -> Option<f64> {
+        return self.volatility_over(YEAR_MS);
+    }
+}
+
+// (log return, elapsed ms) of consecutive samples, samples not later than the previous one are skipped
+fn log_return(last: &mut Option<PriceSample>, sample: PriceSample) -> Option<(f64, f64)> {
+    if sample.price <= 0.0 || (last.is_some() && sample.timestamp_ms <= last.unwrap().timestamp_ms) {
+        return None;
+    }
+    let previous = last.replace(sample);
+    if previous.is_none() {
+        return None;
+    }
+    let previous = previous.unwrap();
+    return Some(((sample.price / previous.price).ln(), (sample.timestamp_ms - previous.timestamp_ms) as f64));
+}
+
+// realized variance of the last length log returns, sum of squared returns over elapsed time
+pub struct RealizedVolatility {
+    returns: VecDeque<(f64, f64)>,
+    length: usize,
+    last: Option<PriceSample>,
+}
+
+impl RealizedVolatility {
+    pub fn new(length: usize) -> RealizedVolatility {
+        return RealizedVolatility{ returns: VecDeque::with_capacity(length + 1), length, last: None }
+    }
+}
+
+impl VolatilityEstimator for RealizedVolatility {
+    type Sample = PriceSample;
+
+    fn update(&mut self, sample: PriceSample) {
+        let r = log_return(&mut self.last, sample);
+        if r.is_some() {
+            self.returns.push_back(r.unwrap());
+            if self.returns.len() > self.length {
+                self.returns.pop_front();
+            }
+        }
+    }
+
+    fn variance_per_ms(&self) -> Option<f64> {
+        if self.returns.len() < self.length || self.length == 0 {
+            return None;
+        }
+        let squared: f64 = self.returns.iter().map(|x| x.0 * x.0).sum();
+        let elapsed: f64 = self.returns.iter().map(|x| x.1).sum();
+        return Some(squared / elapsed);
+    }
+}
+
+// exponentially weighted variance rate, lambda is the weight of the previous estimate after unit_ms,
+// a sample coming after a longer gap decays the previous estimate more
+pub struct EwmaVolatility {
+    lambda: f64,
+    unit_ms: f64,
+    min_samples: usize,
+    samples: usize,
+    variance: Option<f64>,
+    last: Option<PriceSample>,
+}
+
+impl EwmaVolatility {
+    pub fn new(lambda: f64, unit_ms: f64, min_samples: usize) -> EwmaVolatility {
+        return EwmaVolatility{ lambda, unit_ms, min_samples, samples: 0, variance: None, last: None }
+    }
+
+    // RiskMetrics decay per unit_ms, i.e. the usual sampling interval
+    pub fn riskmetrics(unit_ms: f64, min_samples: usize) -> EwmaVolatility {
+        return EwmaVolatility::new(0.94, unit_ms, min_samples);
+    }
+}
+
+impl VolatilityEstimator for EwmaVolatility {
+    type Sample = PriceSample;
+
+    fn update(&mut self, sample: PriceSample) {
+        let r = log_return(&mut self.last, sample);
+        if r.is_none() {
+            return;
+        }
+        let (r, elapsed) = r.unwrap();
+        let rate = r * r / elapsed;
+        let decay = self.lambda.powf(elapsed / self.unit_ms.max(1.0));
+        self.variance = Some(match self.variance {
+            Some(x) => decay * x + (1.0 - decay) * rate,
+            None => rate,
+        });
+        self.samples += 1;
+    }
+
+    fn variance_per_ms(&self) -> Option<f64> {
+        return if self.samples >= self.min_samples {self.variance} else {None};
+    }
+}
+
+// Parkinson estimator from high and low of the last length bars
+pub struct ParkinsonVolatility {
+    // (squared log of high over low, bar duration)
+    bars: VecDeque<(f64, f64)>,
+    length: usize,
+}
+
+impl ParkinsonVolatility {
+    pub fn new(length: usize) -> ParkinsonVolatility {
+        return ParkinsonVolatility{ bars: VecDeque::with_capacity(length + 1), length }
+    }
+}
+
+impl VolatilityEstimator for ParkinsonVolatility {
+    type Sample = OhlcBar;
+
+    fn update(&mut self, bar: OhlcBar) {
+        if bar.low <= 0.0 || bar.high < bar.low || bar.duration_ms == 0 {
+            return;
+        }
+        let range = (bar.high / bar.low).ln();
+        self.bars.push_back((range * range, bar.duration_ms as f64));
+        if self.bars.len() > self.length {
+            self.bars.pop_front();
+        }
+    }
+
+    fn variance_per_ms(&self) -> Option<f64> {
+        if self.bars.len() < self.length || self.length == 0 {
+            return None;
+        }
+        let squared: f64 = self.bars.iter().map(|x| x.0).sum();
+        let elapsed: f64 = self.bars.iter().map(|x| x.1).sum();
+        return Some(squared / (4.0 * 2f64.ln() * elapsed));
+    }
+}
+
+// GARCH(1,1) of the variance rate, h = omega + alpha * r^2 / dt + beta * h, starts at the long run variance
+pub struct GarchVolatility {
+    omega: f64,
+    alpha: f64,
+    beta: f64,
+    min_samples: usize,
+    samples: usize,
+    variance: f64,
+    last: Option<PriceSample>,
+}
+
+impl GarchVolatility {
+    // alpha + beta below 1
+    pub fn new(omega: f64, alpha: f64, beta: f64, min_samples: usize) -> GarchVolatility {
+        let variance = omega / (1.0 - alpha - beta);
+        return GarchVolatility{ omega, alpha, beta, min_samples, samples: 0, variance, last: None }
+    }
+
+    // omega from long run variance per ms
+    pub fn with_long_run(long_run: f64, alpha: f64, beta: f64, min_samples: usize) -> GarchVolatility {
+        return GarchVolatility::new(long_run * (1.0 - alpha - beta), alpha, beta, min_samples);
+    }
+
+    pub fn long_run(&self) -> f64 {
+        return self.omega / (1.0 - self.alpha - self.beta);
+    }
+
+    // expected variance rate after steps more samples
+    pub fn forecast(&self, steps: u32) -> f64 {
+        return self.long_run() + (self.alpha + self.beta).powi(steps as i32) * (self.variance - self.long_run());
+    }
+}
+
+impl VolatilityEstimator for GarchVolatility {
+    type Sample = PriceSample;
+
+    fn update(&mut self, sample: PriceSample) {
+        let r = log_return(&mut self.last, sample);
+        if r.is_none() {
+            return;
+        }
+        let (r, elapsed) = r.unwrap();
+        self.variance = self.omega + self.alpha * r * r / elapsed + self.beta * self.variance;
+        self.samples += 1;
+    }
+
+    fn variance_per_ms(&self) -> Option<f64> {
+        return if self.samples >= self.min_samples {Some(self.variance)} else {None};
+    }
+}
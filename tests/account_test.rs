@@ -0,0 +1,22 @@
+use sui_mm::account::AccountNames;
+use sui_types::base_types::ObjectID;
+
+#[test]
+fn account_names_test() {
+    let (a, b, other) = (ObjectID::random(), ObjectID::random(), ObjectID::random());
+    let owned = vec![a, b];
+    let mut names = AccountNames::default();
+    names.insert("maker".to_string(), b);
+    names.insert("gone".to_string(), other);
+
+    assert_eq!(names.resolve(None, &owned), Some(a));
+    assert_eq!(names.resolve(Some("maker"), &owned), Some(b));
+    assert_eq!(names.resolve(Some(&a.to_string()), &owned), Some(a));
+    assert_eq!(names.resolve(Some("gone"), &owned), None);
+    assert_eq!(names.resolve(Some("unknown"), &owned), None);
+    assert_eq!(names.name_of(&b), Some(&"maker".to_string()));
+
+    let json = serde_json::to_string(&names).unwrap();
+    let parsed: AccountNames = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.accounts, names.accounts);
+}
@@ -0,0 +1,82 @@
+use sui_mm::breaker::{http_route, BreakerConfig, CircuitBreaker, KillSwitch, Trip};
+use sui_mm::pnl::{Fill, PnlEngine};
+use sui_mm::transaction::SubmitResult;
+
+#[test]
+fn loss_and_cooldown_test() {
+    let config = BreakerConfig{ max_loss: 10_000_000.0, loss_window_ms: 60_000, cooldown_ms: 30_000, ..BreakerConfig::default() };
+    let mut breaker = CircuitBreaker::new(config, 0);
+    breaker.record_pnl(1_000, 100_000_000.0);
+    breaker.record_pnl(2_000, 95_000_000.0);
+    assert_eq!(breaker.check(2_000, None, 0, 0, 1_000_000), None);
+    breaker.record_pnl(3_000, 85_000_000.0);
+    assert_eq!(breaker.check(3_000, None, 0, 0, 1_000_000), Some(Trip::Loss { loss: 15_000_000.0, max: 10_000_000.0 }));
+    assert!(breaker.paused(10_000));
+    assert!(!breaker.paused(33_000));
+    assert_eq!(breaker.loss(), 0.0);
+    // high outside of window does not count
+    breaker.record_pnl(40_000, 100_000_000.0);
+    breaker.record_pnl(120_000, 85_000_000.0);
+    assert_eq!(breaker.loss(), 0.0);
+}
+
+#[test]
+fn realized_loss_test() {
+    let config = BreakerConfig{ max_loss: 10_000_000.0, ..BreakerConfig::default() };
+    let mut breaker = CircuitBreaker::new(config, 0);
+    let mut engine = PnlEngine::default();
+    let fill = |timestamp_ms: u64, price: u64, is_buy: bool| Fill{ timestamp_ms, price, quantity: 100_000_000_000, is_buy, is_maker: true, fee: 0 };
+    // 100 SUI bought at 1.0, price falls to 0.5, unrealized loss does not trip
+    engine.apply(&fill(1_000, 1_000_000_000, true));
+    engine.mark(2_000, 500_000_000.0);
+    breaker.record_pnl(2_000, engine.net());
+    assert_eq!(breaker.check(2_000, None, 0, 0, 1_000_000), None);
+    // sold at 0.5, 50 USDC realized
+    engine.apply(&fill(3_000, 500_000_000, false));
+    breaker.record_pnl(3_000, engine.net());
+    assert_eq!(breaker.check(3_000, None, 0, 0, 1_000_000), Some(Trip::Loss { loss: 50_000_000.0, max: 10_000_000.0 }));
+}
+
+#[test]
+fn breakers_test() {
+    let config = BreakerConfig{ max_oracle_stale_ms: 10_000, rpc_window: 4, max_rpc_error_rate: 0.5, max_consecutive_failures: 3, max_mid_divergence: 0.05, ..BreakerConfig::default() };
+    let mut breaker = CircuitBreaker::new(config, 0);
+    breaker.record_oracle(5_000, true);
+    breaker.record_oracle(12_000, false);
+    assert_eq!(breaker.check(12_000, None, 0, 0, 1_000_000), None);
+    assert_eq!(breaker.check(16_000, None, 0, 0, 1_000_000), Some(Trip::OracleStale { age_ms: 11_000 }));
+
+    let mut breaker = CircuitBreaker::new(config, 0);
+    breaker.record_rpc(true);
+    breaker.record_rpc(false);
+    breaker.record_rpc(false);
+    assert_eq!(breaker.check(0, None, 0, 0, 1_000_000), None);
+    breaker.record_submit(&SubmitResult::Timeout);
+    assert_eq!(breaker.check(0, None, 0, 0, 1_000_000), Some(Trip::RpcErrors { rate: 0.75 }));
+
+    let mut breaker = CircuitBreaker::new(config, 0);
+    for _ in 0..3 {
+        breaker.record_submit(&SubmitResult::NoGas);
+    }
+    assert_eq!(breaker.check(0, None, 0, 0, 1_000_000), Some(Trip::ConsecutiveFailures(3)));
+
+    let mut breaker = CircuitBreaker::new(config, 0);
+    assert_eq!(breaker.check(0, Some(1.0), 990_000, 1_010_000, 1_000_000), None);
+    assert!(matches!(breaker.check(0, Some(1.0), 1_090_000, 1_110_000, 1_000_000), Some(Trip::MidDivergence { .. })));
+}
+
+#[test]
+fn kill_switch_test() {
+    assert_eq!(http_route("POST /kill HTTP/1.1\r\nHost: localhost\r\n\r\n"), Some("kill"));
+    assert_eq!(http_route("GET /status HTTP/1.1\r\n\r\n"), Some("status"));
+    assert_eq!(http_route("GET /kill HTTP/1.1\r\n\r\n"), None);
+
+    let path = std::env::temp_dir().join(format!("sui_mm_kill_{}", std::process::id()));
+    let kill_switch = KillSwitch::new(Some(path.to_string_lossy().to_string()));
+    assert!(!kill_switch.is_killed());
+    std::fs::write(&path, "").unwrap();
+    assert!(kill_switch.is_killed());
+    std::fs::remove_file(&path).unwrap();
+    // stays killed once triggered
+    assert!(kill_switch.is_killed());
+}
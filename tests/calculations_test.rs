@@ -0,0 +1,18 @@
+use sui_mm::calculations::{anchor_price, confidence_spread, momentum, PriceAnchor, QuoteOptions};
+use sui_mm::oracle::ValidatedPrice;
+
+#[test]
+fn quote_options_test() {
+    let price = ValidatedPrice{ price: 1.1, conf: 0.011, ema_price: 1.0, ema_conf: 0.002, publish_time: 0 };
+    assert!((momentum(&price) - 0.1).abs() < 1e-9);
+
+    let spot = QuoteOptions::default();
+    assert_eq!(anchor_price(&price, &spot), 1.1);
+    assert_eq!(confidence_spread(&price, &spot), 0.0);
+
+    let ema = QuoteOptions{ anchor: "ema".parse().unwrap(), conf_multiplier: 2.0, momentum_weight: 0.5 };
+    assert_eq!(ema.anchor, PriceAnchor::Ema);
+    assert!((anchor_price(&price, &ema) - 1.05).abs() < 1e-9);
+    // 0.2 percent conf times 2
+    assert!((confidence_spread(&price, &ema) - 0.4).abs() < 1e-9);
+}
@@ -0,0 +1,86 @@
+use sui_mm::candles::{bucket, to_csv, Candle, CandleBuilder, CSV_HEADER};
+use sui_mm::events::OrderFilledEvent;
+use sui_mm::oracle::ValidatedPrice;
+use sui_sdk::rpc_types::SuiEvent;
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::digests::TransactionDigest;
+use sui_types::event::EventID;
+use sui_types::parse_sui_struct_tag;
+
+const POOL: &str = "0x4405b50d791fd3346754e8171aaab6bc2ed26c2c46efdd033c14b30ae507ac33";
+const OTHER_POOL: &str = "0x7f526b1263c4b91b43c9e646419b5696f424de28dda3c1e6658cc0a54558baa7";
+const START: u64 = 1_709_251_200_000;
+
+fn filled(pool: &str, timestamp_ms: u64, price: u64, quantity: u64) -> SuiEvent {
+    let event = OrderFilledEvent{ pool_id: pool.parse().unwrap(), order_id: 1, taker_client_order_id: 1, maker_client_order_id: 2,
+        original_quantity: quantity, base_asset_quantity_filled: quantity, base_asset_quantity_remaining: 0, is_bid: true,
+        maker_address: SuiAddress::ZERO, taker_address: SuiAddress::ZERO, price, taker_commission: 0, maker_rebates: 0 };
+    return SuiEvent{
+        id: EventID{ tx_digest: TransactionDigest::random(), event_seq: 0 },
+        package_id: ObjectID::from_hex_literal("0xdee9").unwrap(),
+        transaction_module: "clob_v2".parse().unwrap(),
+        sender: SuiAddress::ZERO,
+        type_: parse_sui_struct_tag("0xdee9::clob_v2::OrderFilled<0x2::sui::SUI, 0x2::sui::SUI>").unwrap(),
+        parsed_json: serde_json::to_value(&event).unwrap(),
+        bcs: vec![],
+        timestamp_ms: Some(timestamp_ms),
+    }
+}
+
+#[test]
+fn candle_builder_test() {
+    let mut builder = CandleBuilder::new(60_000, 10);
+    // backfill comes most recent first
+    builder.add(START + 70_000, 1.2, 1.0);
+    builder.add(START + 50_000, 1.1, 3.0);
+    builder.add(START + 10_000, 1.0, 1.0);
+    builder.add(START + 30_000, 0.9, 2.0);
+    builder.add(START + 65_000, 1.3, 1.0);
+    let candles = builder.candles();
+    assert_eq!(candles.len(), 2);
+    assert_eq!((candles[0].start_ms, candles[0].open, candles[0].high, candles[0].low, candles[0].close), (START, 1.0, 1.1, 0.9, 1.1));
+    assert_eq!((candles[0].volume, candles[0].count), (6.0, 3));
+    assert!((candles[0].notional - 6.1).abs() < 1e-12);
+    assert_eq!((candles[1].open, candles[1].close, candles[1].high, candles[1].low), (1.3, 1.2, 1.3, 1.2));
+    assert!((candles[0].vwap().unwrap() - 6.1 / 6.0).abs() < 1e-12);
+    assert_eq!(builder.closed(START + 119_999).len(), 1);
+    assert_eq!(builder.closed(START + 120_000).len(), 2);
+
+    let bar = candles[0].bar();
+    assert_eq!((bar.start_ms, bar.duration_ms, bar.high, bar.low), (START, 60_000, 1.1, 0.9));
+}
+
+#[test]
+fn fill_candles_test() {
+    let pool_id: ObjectID = POOL.parse().unwrap();
+    let mut builder = CandleBuilder::new(60_000, 10);
+    // 2.5 SUI at 1.7837 and 1 SUI at 1.7840 USDC
+    assert!(builder.add_fill(&filled(POOL, START + 1_000, 1_783_700, 2_500_000_000), pool_id, 1_000_000, 1_000_000_000));
+    assert!(builder.add_fill(&filled(POOL, START + 2_000, 1_784_000, 1_000_000_000), pool_id, 1_000_000, 1_000_000_000));
+    assert!(!builder.add_fill(&filled(OTHER_POOL, START + 3_000, 2_000_000, 1_000_000_000), pool_id, 1_000_000, 1_000_000_000));
+    let candles = builder.candles();
+    assert_eq!(candles.len(), 1);
+    assert_eq!((candles[0].open, candles[0].close, candles[0].volume, candles[0].count), (1.7837, 1.784, 3.5, 2));
+    assert!((candles[0].notional - (2.5 * 1.7837 + 1.784)).abs() < 1e-12);
+}
+
+#[test]
+fn oracle_candles_test() {
+    let mut builder = CandleBuilder::new(300_000, 2);
+    let price = ValidatedPrice{ price: 1.5, conf: 0.001, ema_price: 1.5, ema_conf: 0.001, publish_time: (START / 1000) as i64 };
+    for i in 0..3u64 {
+        builder.add_price(&ValidatedPrice{ price: price.price + i as f64 / 10.0, publish_time: price.publish_time + i as i64 * 300, ..price });
+    }
+    // only the last 2 candles are kept
+    let candles = builder.candles();
+    assert_eq!(candles.iter().map(|x| x.start_ms).collect::<Vec<u64>>(), vec![START + 300_000, START + 600_000]);
+    assert_eq!((candles[0].volume, candles[0].count, candles[0].vwap()), (0.0, 1, None));
+    assert_eq!(bucket(START + 299_999, 300_000), START);
+}
+
+#[test]
+fn csv_test() {
+    let candle = Candle{ start_ms: START, interval_ms: 60_000, open: 1.0, high: 1.5, low: 0.5, close: 1.25, volume: 2.0, notional: 2.5, count: 2 };
+    assert_eq!(to_csv(&vec![candle]), format!("{}\n{},60000,1,1.5,0.5,1.25,2,2.5,2\n", CSV_HEADER, START));
+    assert_eq!(to_csv(&vec![]), format!("{}\n", CSV_HEADER));
+}
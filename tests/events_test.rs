@@ -0,0 +1,76 @@
+use serde_json::json;
+use sui_mm::events::{decode, AllOrdersCanceledEvent, AssetEvent, CanceledOrder, DeepBookEvent, OrderCanceledEvent, Payload};
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::{parse_sui_struct_tag, parse_sui_type_tag};
+
+const POOL: &str = "0x4405b50d791fd3346754e8171aaab6bc2ed26c2c46efdd033c14b30ae507ac33";
+const OWNER: &str = "0xf995d6df20e18421928ff0648bd583ccdf384ab05791d8be21d32977a37dacfc";
+const USDC: &str = "0x5d4b302506645c37ff133b98c4b50a5ae14841659738d6d733d59d0d217a93bf::coin::COIN";
+
+#[test]
+fn decode_json_test() {
+    let type_ = parse_sui_struct_tag(&format!("0xdee9::clob_v2::OrderPlaced<0x2::sui::SUI, {USDC}>")).unwrap();
+    let json = json!({"pool_id": POOL, "order_id": "5925000", "client_order_id": "4399", "is_bid": true, "owner": OWNER,
+        "original_quantity": "5000000000000", "base_asset_quantity_placed": "4300000000000", "price": "1783700", "expire_timestamp": "1708380292894"});
+    let decoded = decode(&type_, Payload::Json(&json)).unwrap();
+    assert_eq!(decoded.base_asset(), Some(&parse_sui_type_tag("0x2::sui::SUI").unwrap()));
+    assert_eq!(decoded.quote_asset(), Some(&parse_sui_type_tag(USDC).unwrap()));
+    assert_eq!(decoded.event.pool_id(), POOL.parse::<ObjectID>().unwrap());
+    match decoded.event {
+        DeepBookEvent::OrderPlaced(x) => {
+            assert_eq!((x.order_id, x.price, x.base_asset_quantity_placed), (5_925_000, 1_783_700, 4_300_000_000_000));
+            assert_eq!(x.owner, OWNER.parse::<SuiAddress>().unwrap());
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let type_ = parse_sui_struct_tag(&format!("0xdee9::clob_v2::AllOrdersCanceled<0x2::sui::SUI, {USDC}>")).unwrap();
+    let json = json!({"pool_id": POOL, "orders_canceled": [{"client_order_id": "1", "order_id": "2", "is_bid": false, "owner": OWNER,
+        "original_quantity": "100", "base_asset_quantity_canceled": "50", "price": "1783800"}]});
+    let decoded = decode(&type_, Payload::Json(&json)).unwrap();
+    assert!(matches!(decoded.event, DeepBookEvent::AllOrdersCanceled(ref x) if x.orders_canceled.len() == 1 && x.orders_canceled[0].price == 1_783_800));
+
+    let type_ = parse_sui_struct_tag("0xdee9::clob_v2::PoolCreated").unwrap();
+    let json = json!({"base_asset": {"name": "0000000000000000000000000000000000000000000000000000000000000002::sui::SUI"},
+        "quote_asset": {"name": "5d4b302506645c37ff133b98c4b50a5ae14841659738d6d733d59d0d217a93bf::coin::COIN"},
+        "pool_id": POOL, "tick_size": "100", "lot_size": "100000000", "taker_fee_rate": "2500000", "maker_rebate_rate": "1500000"});
+    let decoded = decode(&type_, Payload::Json(&json)).unwrap();
+    assert_eq!(decoded.base_asset(), None);
+    assert!(matches!(decoded.event, DeepBookEvent::PoolCreated(ref x) if x.lot_size == 100_000_000));
+
+    let type_ = parse_sui_struct_tag("0xdee9::clob_v2::DepositAsset<0x2::sui::SUI>").unwrap();
+    let json = json!({"pool_id": POOL, "quantity": "1000000000", "owner": OWNER});
+    let decoded = decode(&type_, Payload::Json(&json)).unwrap();
+    assert_eq!((decoded.type_params.len(), decoded.base_asset()), (1, None));
+    assert!(matches!(decoded.event, DeepBookEvent::DepositAsset(ref x) if x.quantity == 1_000_000_000));
+
+    // other packages and malformed payloads
+    let type_ = parse_sui_struct_tag("0x2::coin::CoinMetadata<0x2::sui::SUI>").unwrap();
+    assert_eq!(decode(&type_, Payload::Json(&json)), None);
+    let type_ = parse_sui_struct_tag("0xdee9::clob_v2::WithdrawAsset<0x2::sui::SUI>").unwrap();
+    assert_eq!(decode(&type_, Payload::Json(&json!({"pool_id": POOL}))), None);
+}
+
+#[test]
+fn decode_bcs_test() {
+    let pool_id = POOL.parse::<ObjectID>().unwrap();
+    let owner = OWNER.parse::<SuiAddress>().unwrap();
+    let canceled = OrderCanceledEvent{ pool_id, order_id: 7, client_order_id: 8, is_bid: true, owner, original_quantity: 100,
+        base_asset_quantity_canceled: 40, price: 1_783_700 };
+    let type_ = parse_sui_struct_tag(&format!("0xdee9::clob_v2::OrderCanceled<0x2::sui::SUI, {USDC}>")).unwrap();
+    let bytes = bcs::to_bytes(&canceled).unwrap();
+    assert_eq!(decode(&type_, Payload::Bcs(&bytes)).unwrap().event, DeepBookEvent::OrderCanceled(canceled.clone()));
+    // json and bcs of the same event decode the same
+    let json = serde_json::to_value(&canceled).unwrap();
+    assert_eq!(json.get("price").unwrap(), "1783700");
+    assert_eq!(decode(&type_, Payload::Json(&json)), decode(&type_, Payload::Bcs(&bytes)));
+
+    let all = AllOrdersCanceledEvent{ pool_id, orders_canceled: vec![CanceledOrder{ client_order_id: 1, order_id: 2, is_bid: false, owner,
+        original_quantity: 100, base_asset_quantity_canceled: 100, price: 1_783_800 }] };
+    let type_ = parse_sui_struct_tag(&format!("0xdee9::clob_v2::AllOrdersCanceled<0x2::sui::SUI, {USDC}>")).unwrap();
+    assert_eq!(decode(&type_, Payload::Bcs(&bcs::to_bytes(&all).unwrap())).unwrap().event, DeepBookEvent::AllOrdersCanceled(all));
+
+    let withdraw = AssetEvent{ pool_id, quantity: 5, owner };
+    let type_ = parse_sui_struct_tag("0xdee9::clob_v2::WithdrawAsset<0x2::sui::SUI>").unwrap();
+    assert_eq!(decode(&type_, Payload::Bcs(&bcs::to_bytes(&withdraw).unwrap())).unwrap().event, DeepBookEvent::WithdrawAsset(withdraw));
+}
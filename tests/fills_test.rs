@@ -0,0 +1,36 @@
+use serde_json::json;
+use sui_mm::events::OrderFilledEvent;
+use sui_mm::fills::{load_cursor, save_cursor};
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::digests::TransactionDigest;
+use sui_types::event::EventID;
+
+#[test]
+fn order_filled_event_test() {
+    let json = json!({"base_asset_quantity_filled": "700000000000", "base_asset_quantity_remaining": "2700000000000", "is_bid": true,
+        "maker_address": "0xf995d6df20e18421928ff0648bd583ccdf384ab05791d8be21d32977a37dacfc", "maker_client_order_id": "1708380292894207686",
+        "maker_rebates": "249718", "order_id": "5925000", "original_quantity": "5000000000000",
+        "pool_id": "0x4405b50d791fd3346754e8171aaab6bc2ed26c2c46efdd033c14b30ae507ac33", "price": "1783700",
+        "taker_address": "0x11f8f568d871ff0cf829aca81e51a06a6869d12abe0b3351b914a4673ea3d857", "taker_client_order_id": "4399", "taker_commission": "249718"});
+    let event: OrderFilledEvent = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(event.price, 1_783_700);
+    assert_eq!(event.maker_client_order_id, 1_708_380_292_894_207_686);
+    assert_eq!(event.pool_id, "0x4405b50d791fd3346754e8171aaab6bc2ed26c2c46efdd033c14b30ae507ac33".parse::<ObjectID>().unwrap());
+    assert_eq!(event.taker_address, "0x11f8f568d871ff0cf829aca81e51a06a6869d12abe0b3351b914a4673ea3d857".parse::<SuiAddress>().unwrap());
+    assert_eq!(serde_json::to_value(&event).unwrap(), json);
+
+    let bcs_bytes = bcs::to_bytes(&event).unwrap();
+    // 2 addresses, 1 id, 9 u64 and a bool
+    assert_eq!(bcs_bytes.len(), 3 * 32 + 9 * 8 + 1);
+    assert_eq!(bcs::from_bytes::<OrderFilledEvent>(&bcs_bytes).unwrap(), event);
+}
+
+#[test]
+fn cursor_test() {
+    let path = std::env::temp_dir().join(format!("sui_mm_cursor_{}.json", std::process::id())).to_string_lossy().to_string();
+    assert_eq!(load_cursor(&path), None);
+    let cursor = EventID{ tx_digest: TransactionDigest::random(), event_seq: 3 };
+    save_cursor(&path, &cursor);
+    assert_eq!(load_cursor(&path), Some(cursor));
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,39 @@
+use sui_mm::gas::{dust_count, largest_coin, merge_and_split_transaction, select_coin, PooledCoin};
+use sui_types::base_types::{ObjectID, SequenceNumber, SuiAddress};
+use sui_types::digests::ObjectDigest;
+use sui_types::transaction::Command;
+
+fn coin(balance: u64, reserved: bool) -> PooledCoin {
+    return PooledCoin{ object_ref: (ObjectID::random(), SequenceNumber::from_u64(1), ObjectDigest::random()), balance, reserved };
+}
+
+#[test]
+fn select_coin_test() {
+    let coins = vec![coin(1_000_000_000, true), coin(10_000_000, false), coin(600_000_000, false)];
+    assert_eq!(select_coin(&coins, 50_000_000), Some(2));
+    assert_eq!(select_coin(&coins, 5_000_000), Some(1));
+    assert_eq!(select_coin(&coins, 700_000_000), None);
+    // reserved coin is bigger but not free
+    assert_eq!(largest_coin(&coins, 50_000_000), Some(2));
+    assert_eq!(largest_coin(&coins, 700_000_000), None);
+    assert_eq!(largest_coin(&vec![], 0), None);
+}
+
+#[test]
+fn merge_and_split_test() {
+    let dust = vec![coin(1_000, false).object_ref, coin(2_000, false).object_ref];
+    let ptx = merge_and_split_transaction(SuiAddress::ZERO, &dust, 3, 1_000_000_000);
+    assert!(matches!(ptx.commands[0], Command::MergeCoins(_, _)));
+    assert!(matches!(ptx.commands[1], Command::SplitCoins(_, _)));
+
+    let ptx = merge_and_split_transaction(SuiAddress::ZERO, &vec![], 2, 1_000_000_000);
+    assert!(matches!(ptx.commands[0], Command::SplitCoins(_, _)));
+}
+
+#[test]
+fn dust_count_test() {
+    let coins = vec![coin(1_000, false), coin(2_000, true), coin(40_000_000, false), coin(1_000_000_000, false)];
+    // reserved coins are in flight and cannot be merged
+    assert_eq!(dust_count(&coins, 50_000_000), 2);
+    assert_eq!(dust_count(&vec![], 50_000_000), 0);
+}
@@ -0,0 +1,55 @@
+use sui_mm::calculations::Result as Quotes;
+use sui_mm::inventory::{apply_inventory_limits, flatten_order, inventory_state, Inventory, InventoryConfig, InventoryState};
+use sui_mm::risk::OrderRequest;
+
+#[test]
+fn inventory_state_test() {
+    // 2 USDC per SUI, 100 SUI and 100 USDC are 150 SUI in total
+    let inventory = Inventory::new(&vec![60_000_000_000, 20_000_000_000, 50_000_000, 0], 20_000_000_000, 50_000_000);
+    assert_eq!(inventory, Inventory{ base: 100_000_000_000, quote: 100_000_000 });
+    assert_eq!(inventory.total_in_base(2.0, 1_000_000_000, 1_000_000), 150_000_000_000.0);
+
+    let units = InventoryConfig{ min: Some(10_000_000_000.0), max: Some(80_000_000_000.0), hard_max: Some(120_000_000_000.0), ..InventoryConfig::default() };
+    assert_eq!(inventory_state(&inventory, 2.0, 1_000_000_000, 1_000_000, &units), InventoryState::Long);
+    let long = Inventory{ base: 130_000_000_000, quote: 0 };
+    assert_eq!(inventory_state(&long, 2.0, 1_000_000_000, 1_000_000, &units), InventoryState::HardLong(50_000_000_000));
+    let short = Inventory{ base: 5_000_000_000, quote: 0 };
+    assert_eq!(inventory_state(&short, 2.0, 1_000_000_000, 1_000_000, &units), InventoryState::Short);
+
+    let fraction = InventoryConfig{ fraction: true, min: Some(0.3), max: Some(0.7), hard_min: Some(0.2), hard_max: Some(0.9), ..InventoryConfig::default() };
+    assert_eq!(inventory_state(&inventory, 2.0, 1_000_000_000, 1_000_000, &fraction), InventoryState::Normal);
+    // 20 SUI of 170 SUI total, buy back to 30 percent
+    let short = Inventory{ base: 20_000_000_000, quote: 300_000_000 };
+    let state = inventory_state(&short, 2.0, 1_000_000_000, 1_000_000, &fraction);
+    assert!(matches!(state, InventoryState::HardShort(x) if x > 30_999_000_000 && x < 31_001_000_000));
+}
+
+#[test]
+fn apply_inventory_limits_test() {
+    let quotes = || Quotes{ ask_price: 2_010_000, ask_quantity: 1_000_000_000, bid_price: 1_990_000, bid_quantity: 1_000_000_000, duration_ms: 60_000 };
+    let mut long = quotes();
+    apply_inventory_limits(&mut long, InventoryState::Long);
+    assert_eq!((long.bid_quantity, long.ask_quantity), (0, 1_000_000_000));
+    let mut short = quotes();
+    apply_inventory_limits(&mut short, InventoryState::Short);
+    assert_eq!((short.bid_quantity, short.ask_quantity), (1_000_000_000, 0));
+    let mut normal = quotes();
+    apply_inventory_limits(&mut normal, InventoryState::Normal);
+    assert_eq!((normal.bid_quantity, normal.ask_quantity), (1_000_000_000, 1_000_000_000));
+}
+
+#[test]
+fn flatten_order_test() {
+    let balance_data = vec![30_000_000_000, 0, 15_000_000, 0];
+    // sell capped by 30 SUI in custodian at 2.0 minus 50 percent
+    let order = flatten_order(InventoryState::HardLong(50_000_000_000), &balance_data, 2.0, 1_000_000, 1_000_000_000, 1_000_000, 1_000, 100_000_000, 0.5);
+    assert_eq!(order, Some(OrderRequest{ price: 1_000_000, quantity: 30_000_000_000, is_bid: false }));
+    // 15 USDC buys 5 SUI at 3.0
+    let order = flatten_order(InventoryState::HardShort(10_000_000_000), &balance_data, 2.0, 1_000_000, 1_000_000_000, 1_000_000, 1_000, 100_000_000, 0.5);
+    assert_eq!(order, Some(OrderRequest{ price: 3_000_000, quantity: 5_000_000_000, is_bid: true }));
+    // quote with 8 decimals and deepbook price scaled by 1000, 15 units of quote still buy 5 SUI
+    let order = flatten_order(InventoryState::HardShort(10_000_000_000), &vec![0, 0, 1_500_000_000, 0], 2.0, 1_000, 1_000_000_000, 100_000_000, 1, 100_000_000, 0.5);
+    assert_eq!(order, Some(OrderRequest{ price: 3_000, quantity: 5_000_000_000, is_bid: true }));
+    assert_eq!(flatten_order(InventoryState::HardShort(10_000_000_000), &vec![0, 0, 0, 0], 2.0, 1_000_000, 1_000_000_000, 1_000_000, 1_000, 100_000_000, 0.5), None);
+    assert_eq!(flatten_order(InventoryState::Long, &balance_data, 2.0, 1_000_000, 1_000_000_000, 1_000_000, 1_000, 100_000_000, 0.5), None);
+}
@@ -0,0 +1,43 @@
+use serde_json::json;
+use sui_mm::events::OrderFilledEvent;
+use sui_mm::market::{order_filled, vwap};
+use sui_sdk::rpc_types::SuiEvent;
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::digests::TransactionDigest;
+use sui_types::event::EventID;
+use sui_types::parse_sui_struct_tag;
+
+const POOL: &str = "0x4405b50d791fd3346754e8171aaab6bc2ed26c2c46efdd033c14b30ae507ac33";
+
+fn event(name: &str, parsed_json: serde_json::Value) -> SuiEvent {
+    return SuiEvent{
+        id: EventID{ tx_digest: TransactionDigest::random(), event_seq: 0 },
+        package_id: ObjectID::from_hex_literal("0xdee9").unwrap(),
+        transaction_module: "clob_v2".parse().unwrap(),
+        sender: SuiAddress::ZERO,
+        type_: parse_sui_struct_tag(&format!("0xdee9::clob_v2::{}<0x2::sui::SUI, 0x2::sui::SUI>", name)).unwrap(),
+        parsed_json,
+        bcs: vec![],
+        timestamp_ms: Some(1_709_251_200_000),
+    }
+}
+
+fn filled(price: u64, quantity: u64) -> SuiEvent {
+    let filled = OrderFilledEvent{ pool_id: POOL.parse().unwrap(), order_id: 1, taker_client_order_id: 1, maker_client_order_id: 2,
+        original_quantity: quantity, base_asset_quantity_filled: quantity, base_asset_quantity_remaining: 0, is_bid: true,
+        maker_address: SuiAddress::ZERO, taker_address: SuiAddress::ZERO, price, taker_commission: 0, maker_rebates: 0 };
+    return event("OrderFilled", serde_json::to_value(&filled).unwrap());
+}
+
+#[test]
+fn undecodable_fill_test() {
+    let broken = event("OrderFilled", json!({"price": "not a number"}));
+    let other = event("OrderCanceled", json!({}));
+    assert_eq!(order_filled(&filled(1_000_000, 1)).map(|x| x.price), Some(1_000_000));
+    assert!(order_filled(&broken).is_none());
+    assert!(order_filled(&other).is_none());
+    // broken events are skipped instead of panicking
+    let events = vec![filled(1_000_000, 1_000), broken, filled(2_000_000, 3_000)];
+    assert_eq!(vwap(&events), Some((1_750_000.0, 1_000_000, 2_000_000)));
+    assert_eq!(vwap(&vec![event("OrderFilled", json!({}))]), None);
+}
@@ -14,7 +14,7 @@ use sui_sdk::rpc_types::{SuiObjectDataOptions, SuiTransactionBlockResponseOption
 use sui_types::quorum_driver_types::ExecuteTransactionRequestType;
 use shared_crypto::intent::Intent;
 use sui_mm::constant::LIMIT_ORDER_NO_RESTRICTION;
-use sui_mm::market::{get_fills, get_market_price};
+use sui_mm::market::get_market_price;
 use sui_mm::order::{Order, OrderWrapper};
 use sui_mm::pyth::{get_sui_usdc_price, PythFeeder};
 use sui_mm::user::{get_account_balance, get_account_cap, parse_result_account_balance};
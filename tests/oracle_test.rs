@@ -0,0 +1,59 @@
+use sui_mm::constant::{SUI_PRICE_FEED, USDC_PRICE_FEED};
+use sui_mm::oracle::{check_mid_deviation, validate_sui_usdc, FeedPair, OracleConfig, OracleError, PriceOracle};
+use sui_mm::pyth::{EmaPrice, Price, PriceFeed};
+
+fn feed(id: &str, price: &str, conf: &str, publish_time: i64) -> PriceFeed {
+    return PriceFeed{
+        id: id.trim_start_matches("0x").to_string(),
+        price: Price{ price: price.to_string(), conf: conf.to_string(), expo: -8, publish_time },
+        ema_price: EmaPrice{ price: price.to_string(), conf: conf.to_string(), expo: -8, publish_time },
+    }
+}
+
+#[test]
+fn oracle_test() {
+    let config = OracleConfig::default();
+    // usdc first, matching has to be done by id
+    let feeds = vec![
+        feed(USDC_PRICE_FEED, "100000000", "10000", 1000),
+        feed(SUI_PRICE_FEED, "180000000", "90000", 1000),
+    ];
+    let price = validate_sui_usdc(&feeds, &config, 1010).unwrap();
+    assert!((price.price - 1.8).abs() < 1e-9);
+    assert_eq!(price.publish_time, 1000);
+
+    let stale = validate_sui_usdc(&feeds, &config, 1100);
+    assert_eq!(stale, Err(OracleError::Stale { age_s: 100, max_age_s: 30 }));
+
+    let wide = vec![
+        feed(USDC_PRICE_FEED, "100000000", "10000", 1000),
+        feed(SUI_PRICE_FEED, "180000000", "9000000", 1000),
+    ];
+    assert!(matches!(validate_sui_usdc(&wide, &config, 1000), Err(OracleError::WideConfidence { .. })));
+
+    let missing = vec![feed(SUI_PRICE_FEED, "180000000", "90000", 1000)];
+    assert!(matches!(validate_sui_usdc(&missing, &config, 1000), Err(OracleError::MissingFeed(_))));
+
+    assert!(check_mid_deviation(1.8, 1_790_000, 1_810_000, 1_000_000, &config).is_ok());
+    assert!(check_mid_deviation(1.8, 1_690_000, 1_710_000, 1_000_000, &config).is_err());
+}
+
+#[test]
+fn cross_rate_test() {
+    let config = OracleConfig::default();
+    let mut old = feed(USDC_PRICE_FEED, "100000000", "50000", 990);
+    old.ema_price.price = "99000000".to_string();
+    let feeds = vec![feed(SUI_PRICE_FEED, "200000000", "100000", 1000), old];
+    let oracle = PriceOracle::new(FeedPair::ThroughUsd { base: SUI_PRICE_FEED.to_string(), quote: USDC_PRICE_FEED.to_string() }, config);
+    let price = oracle.price_from_feeds(&feeds, 1000).unwrap();
+    assert!((price.price - 2.0).abs() < 1e-9);
+    // 0.0005 + 0.0005 relative
+    assert!((price.conf - 0.002).abs() < 1e-9);
+    assert!((price.ema_price - 2.0 / 0.99).abs() < 1e-9);
+    assert_eq!(price.publish_time, 990);
+
+    let direct = PriceOracle::new(FeedPair::Direct(SUI_PRICE_FEED.to_string()), config);
+    let price = direct.price_from_feeds(&feeds, 1000).unwrap();
+    assert!((price.price - 2.0).abs() < 1e-9);
+    assert_eq!(price.publish_time, 1000);
+}
@@ -0,0 +1,67 @@
+use std::str::FromStr;
+use serde_json::json;
+use sui_mm::events::OrderFilledEvent;
+use sui_mm::pnl::{day_of, own_fills, Fill, PnlEngine};
+use sui_types::base_types::SuiAddress;
+use sui_types::digests::TransactionDigest;
+use sui_types::event::EventID;
+
+#[test]
+fn own_fills_test() {
+    let event: OrderFilledEvent = serde_json::from_value(json!({"base_asset_quantity_filled": "700000000000", "base_asset_quantity_remaining": "2700000000000", "is_bid": true,
+        "maker_address": "0xf995d6df20e18421928ff0648bd583ccdf384ab05791d8be21d32977a37dacfc", "maker_client_order_id": "1708380292894207686",
+        "maker_rebates": "249718", "order_id": "5925000", "original_quantity": "5000000000000",
+        "pool_id": "0x4405b50d791fd3346754e8171aaab6bc2ed26c2c46efdd033c14b30ae507ac33", "price": "1783700",
+        "taker_address": "0x11f8f568d871ff0cf829aca81e51a06a6869d12abe0b3351b914a4673ea3d857", "taker_client_order_id": "4399", "taker_commission": "349718"})).unwrap();
+    let maker = vec![event.maker_address];
+    assert_eq!(own_fills(&event, 1_000, &maker), vec![Fill{ timestamp_ms: 1_000, price: 1_783_700, quantity: 700_000_000_000, is_buy: true, is_maker: true, fee: 249_718 }]);
+    let taker = vec![SuiAddress::from_str("0x11f8f568d871ff0cf829aca81e51a06a6869d12abe0b3351b914a4673ea3d857").unwrap()];
+    let fills = own_fills(&event, 1_000, &taker);
+    assert_eq!(fills.len(), 1);
+    assert!(!fills[0].is_buy && !fills[0].is_maker && fills[0].fee == 349_718);
+    assert_eq!(own_fills(&event, 1_000, &vec![]), vec![]);
+}
+
+#[test]
+fn pnl_engine_test() {
+    let fill = |price: u64, quantity: u64, is_buy: bool| Fill{ timestamp_ms: 1_709_251_200_000, price, quantity, is_buy, is_maker: true, fee: 1_000 };
+    let mut engine = PnlEngine::default();
+    // 10 SUI at 1.0 and 10 SUI at 2.0 USDC
+    engine.apply(&fill(1_000_000, 10_000_000_000, true));
+    engine.apply(&fill(2_000_000, 10_000_000_000, true));
+    assert_eq!(engine.average_cost, 1_500_000.0);
+    assert_eq!(engine.apply(&fill(2_000_000, 5_000_000_000, false)), 2_500_000.0);
+    // closes 15 SUI at a loss and opens 5 SUI short at 1.0
+    assert_eq!(engine.apply(&fill(1_000_000, 20_000_000_000, false)), -7_500_000.0);
+    assert_eq!(engine.position, -5_000_000_000.0);
+    assert_eq!(engine.average_cost, 1_000_000.0);
+    assert_eq!(engine.unrealized(500_000.0), 2_500_000.0);
+    assert_eq!(engine.net(), -5_000_000.0 + 4_000.0);
+
+    assert_eq!(engine.mark(1_709_251_200_000, 500_000.0), -5_000_000.0 + 4_000.0 + 2_500_000.0);
+    let day = engine.days.get("2024-03-01").unwrap();
+    assert_eq!((day.fills, day.realized, day.rebates, day.unrealized), (4, -5_000_000.0, 4_000.0, 2_500_000.0));
+}
+
+#[test]
+fn day_of_test() {
+    assert_eq!(day_of(0), "1970-01-01");
+    assert_eq!(day_of(951_782_400_000), "2000-02-29");
+    assert_eq!(day_of(1_709_251_199_999), "2024-02-29");
+    assert_eq!(day_of(1_709_251_200_000), "2024-03-01");
+}
+
+#[test]
+fn pnl_file_test() {
+    let path = std::env::temp_dir().join(format!("sui_mm_pnl_{}.json", std::process::id())).to_string_lossy().to_string();
+    let mut engine = PnlEngine::default();
+    engine.apply(&Fill{ timestamp_ms: 1_709_251_200_000, price: 1_000_000, quantity: 10_000_000_000, is_buy: true, is_maker: true, fee: 1_000 });
+    engine.cursor = Some(EventID{ tx_digest: TransactionDigest::random(), event_seq: 2 });
+    engine.save(&path);
+    let loaded = PnlEngine::load(&path);
+    assert_eq!((loaded.position, loaded.cursor), (engine.position, engine.cursor));
+    // pnl file without cursor
+    std::fs::write(&path, r#"{"position": 1.0, "average_cost": 2.0, "realized": 0.0, "rebates": 0.0, "commissions": 0.0, "days": {}}"#).unwrap();
+    assert_eq!(PnlEngine::load(&path).cursor, None);
+    std::fs::remove_file(&path).unwrap();
+}
@@ -1,7 +1,9 @@
 use std::env;
 use log::{debug, LevelFilter};
 use sui_sdk::SuiClientBuilder;
-use sui_mm::pool::get_pools_created;
+use sui_mm::constant::SUI_USDC_DECIMALS;
+use sui_mm::pool::{get_pools_created, normalize_type, price_decimals, sort_summaries, PoolRecord, PoolRegistry, PoolSort, PoolSummary};
+use sui_types::base_types::ObjectID;
 
 #[tokio::test]
 async fn pool_test() {
@@ -13,4 +15,77 @@ async fn pool_test() {
     let r = get_pools_created(&client).await;
     debug!("{:?}", r);
     debug!("{}", r.data.len());
-}
\ No newline at end of file
+}
+#[test]
+fn pool_registry_test() {
+    let json = serde_json::json!({
+        "base_asset": {"name": "0000000000000000000000000000000000000000000000000000000000000002::sui::SUI"},
+        "quote_asset": {"name": "5d4b302506645c37ff133b98c4b50a5ae14841659738d6d733d59d0d217a93bf::coin::COIN"},
+        "pool_id": "0x4405b50d791fd3346754e8171aaab6bc2ed26c2c46efdd033c14b30ae507ac33",
+        "tick_size": "100",
+        "lot_size": "100000000",
+        "taker_fee_rate": "2500000",
+        "maker_rebate_rate": "1500000",
+    });
+    let record = PoolRecord::from_json(&json).unwrap();
+    assert_eq!(record.pair(), "SUI/USDC");
+    assert_eq!(record.tick_size, 100);
+    let registry = PoolRegistry{ pools: vec![record.clone()] };
+    assert_eq!(registry.find_by_pair("sui/usdc"), Some(&record));
+    assert_eq!(registry.find_by_pair("0x2::sui::SUI/USDC"), Some(&record));
+    assert_eq!(registry.find_by_pair("USDC/SUI"), None);
+    assert_eq!(normalize_type("0x2::sui::SUI"), normalize_type(&record.base_asset));
+    assert_eq!(registry.resolve_pair("SUI/USDC"), Ok(&record));
+    assert!(registry.resolve_pair("SUI/USDT").is_err());
+}
+
+#[test]
+fn ambiguous_pair_test() {
+    let wormhole = PoolRecord{
+        pool_id: ObjectID::random(),
+        base_asset: normalize_type("0x2::sui::SUI"),
+        quote_asset: normalize_type("0x5d4b302506645c37ff133b98c4b50a5ae14841659738d6d733d59d0d217a93bf::coin::COIN"),
+        tick_size: 100,
+        lot_size: 100_000_000,
+        taker_fee_rate: 2_500_000,
+        maker_rebate_rate: 1_500_000,
+    };
+    let native = PoolRecord{
+        pool_id: ObjectID::random(),
+        quote_asset: normalize_type("0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC"),
+        ..wormhole.clone()
+    };
+    let registry = PoolRegistry{ pools: vec![wormhole.clone(), native.clone()] };
+    assert_eq!(registry.find_all_by_pair("SUI/USDC").len(), 2);
+    let error = registry.resolve_pair("SUI/USDC").unwrap_err();
+    assert!(error.contains(&wormhole.pool_id.to_string()) && error.contains(&native.pool_id.to_string()));
+    assert_eq!(registry.resolve_pair(&format!("SUI/{}", native.quote_asset)), Ok(&native));
+}
+
+#[test]
+fn sort_summaries_test() {
+    let summary = |pair: &str, spread_bps: Option<f64>, volume_24h: u64| PoolSummary{
+        pool_id: ObjectID::random(),
+        pair: pair.to_string(),
+        best_bid: 0.0,
+        best_ask: 0.0,
+        spread_bps,
+        volume_24h,
+        trades_24h: 0,
+    };
+    let mut summaries = vec![summary("B/USDC", None, 10), summary("A/USDC", Some(5.0), 1), summary("C/USDC", Some(2.0), 100)];
+    sort_summaries(&mut summaries, "volume".parse().unwrap());
+    assert_eq!(summaries.iter().map(|x| x.volume_24h).collect::<Vec<u64>>(), vec![100, 10, 1]);
+    sort_summaries(&mut summaries, PoolSort::Spread);
+    assert_eq!(summaries.iter().map(|x| x.pair.as_str()).collect::<Vec<&str>>(), vec!["C/USDC", "A/USDC", "B/USDC"]);
+    sort_summaries(&mut summaries, PoolSort::Pair);
+    assert_eq!(summaries[0].pair, "A/USDC");
+}
+
+#[test]
+fn price_decimals_test() {
+    assert_eq!(price_decimals(9, 6), SUI_USDC_DECIMALS);
+    assert_eq!(price_decimals(6, 9), 1_000_000_000_000);
+    assert_eq!(price_decimals(8, 8), 1_000_000_000);
+    assert_eq!(price_decimals(20, 6), 1);
+}
@@ -0,0 +1,35 @@
+use sui_mm::constant::SUI_PRICE_FEED;
+use sui_mm::oracle::{validate_feed, OracleConfig};
+use sui_mm::pyth_onchain::{decode_price_info_object, PriceIdentifier, PriceInfo, PriceInfoObject, PythI64, PythPrice, PythPriceFeed};
+use sui_types::base_types::ObjectID;
+
+#[test]
+fn decode_price_info_test() {
+    let feed_id = SUI_PRICE_FEED.trim_start_matches("0x");
+    let feed_bytes: Vec<u8> = (0..feed_id.len()).step_by(2).map(|i| u8::from_str_radix(&feed_id[i..i + 2], 16).unwrap()).collect();
+    let price = PythPrice{
+        price: PythI64{ negative: false, magnitude: 180_000_000 },
+        conf: 90_000,
+        expo: PythI64{ negative: true, magnitude: 8 },
+        timestamp: 1000,
+    };
+    let object = PriceInfoObject{
+        id: ObjectID::random(),
+        price_info: PriceInfo{
+            attestation_time: 1000,
+            arrival_time: 1001,
+            price_feed: PythPriceFeed{
+                price_identifier: PriceIdentifier{ bytes: feed_bytes },
+                price: price.clone(),
+                ema_price: price,
+            },
+        },
+    };
+    let bytes = bcs::to_bytes(&object).unwrap();
+    let feed = decode_price_info_object(&bytes).unwrap();
+    assert_eq!(feed.id, feed_id);
+    assert_eq!(feed.price.price, "180000000");
+    assert_eq!(feed.price.expo, -8);
+    let validated = validate_feed(&feed, &OracleConfig::default(), 1000).unwrap();
+    assert!((validated.price - 1.8).abs() < 1e-9);
+}
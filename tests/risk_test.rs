@@ -0,0 +1,35 @@
+use sui_mm::calculations::Result as Quotes;
+use sui_mm::risk::{notional, OrderRequest, RiskChecker, RiskConfig, RiskRejection};
+
+#[test]
+fn risk_check_test() {
+    // 1 USDC = 1_000_000, price 1.0 = 1_000_000, 1 SUI = 1_000_000_000
+    assert_eq!(notional(1_000_000, 1_000_000_000), 1_000_000);
+    let config = RiskConfig{ max_order_notional: 100_000_000, max_open_notional: 150_000_000, max_oracle_deviation: 0.05, max_mid_deviation: 0.05, max_orders_per_minute: 3 };
+    let risk = RiskChecker::new(config, 100, 100_000_000, 1_000_000);
+    let order = OrderRequest{ price: 1_000_000, quantity: 80_000_000_000, is_bid: true };
+    assert_eq!(risk.check(&order, &vec![], &vec![], Some(1.0), 990_000, 1_010_000, 0), Ok(()));
+
+    let misscaled = OrderRequest{ price: 1_000_000_000_000, ..order };
+    assert!(matches!(risk.check(&misscaled, &vec![], &vec![], Some(1.0), 0, 0, 0), Err(RiskRejection::OrderNotional { .. })));
+    let tick = OrderRequest{ price: 1_000_050, ..order };
+    assert!(matches!(risk.check(&tick, &vec![], &vec![], Some(1.0), 0, 0, 0), Err(RiskRejection::TickSize { .. })));
+    let lot = OrderRequest{ quantity: 10_050_000_000, ..order };
+    assert!(matches!(risk.check(&lot, &vec![], &vec![], Some(1.0), 0, 0, 0), Err(RiskRejection::LotSize { .. })));
+    let far = OrderRequest{ price: 1_100_000, quantity: 1_000_000_000, ..order };
+    assert!(matches!(risk.check(&far, &vec![], &vec![], Some(1.0), 0, 0, 0), Err(RiskRejection::OracleBand { .. })));
+    assert!(matches!(risk.check(&far, &vec![], &vec![], None, 990_000, 1_010_000, 0), Err(RiskRejection::MidBand { .. })));
+    // 80 USDC pending plus 80 USDC on the same side
+    assert!(matches!(risk.check(&order, &vec![order], &vec![], Some(1.0), 0, 0, 0), Err(RiskRejection::OpenNotional { .. })));
+}
+
+#[test]
+fn rate_limit_test() {
+    let config = RiskConfig{ max_orders_per_minute: 3, ..RiskConfig::default() };
+    let mut risk = RiskChecker::new(config, 100, 100_000_000, 1_000_000);
+    let quotes = Quotes{ ask_price: 1_010_000, ask_quantity: 1_000_000_000, bid_price: 990_000, bid_quantity: 1_000_000_000, duration_ms: 60_000 };
+    let book = vec![990_000, 1_010_000];
+    assert!(risk.check_quotes(&quotes, &vec![], Some(1.0), &book, 0).is_ok());
+    assert!(matches!(risk.check_quotes(&quotes, &vec![], Some(1.0), &book, 1_000), Err(RiskRejection::RateLimit { .. })));
+    assert!(risk.check_quotes(&quotes, &vec![], Some(1.0), &book, 61_000).is_ok());
+}
@@ -0,0 +1,116 @@
+use sui_mm::calculations::PriceAnchor;
+use sui_mm::oracle::{FeedPair, OracleConfig, ValidatedPrice};
+use sui_mm::order::Order;
+use sui_mm::inventory::Inventory;
+use sui_mm::risk::OrderRequest;
+use sui_mm::runner::{apply_inventory, decide, transaction_batches, PoolConfig, QuoteAction, QuoteVolatility};
+use sui_mm::stats::MarketSnapshot;
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+#[test]
+fn pool_config_test() {
+    let pools: Vec<PoolConfig> = serde_json::from_str(r#"[
+        {"pool": "0x4405b50d791fd3346754e8171aaab6bc2ed26c2c46efdd033c14b30ae507ac33"},
+        {"pool": "0x4405b50d791fd3346754e8171aaab6bc2ed26c2c46efdd033c14b30ae507ac33", "direct_feed": "0xabc", "anchor": "ema", "price_decimals": 1000}
+    ]"#).unwrap();
+    assert_eq!(pools[0].feed_pair(), FeedPair::sui_usdc());
+    assert_eq!(pools[0].anchor, PriceAnchor::Spot);
+    assert_eq!(pools[0].price_decimals, 1_000_000);
+    assert_eq!(pools[1].feed_pair(), FeedPair::Direct("0xabc".to_string()));
+    assert_eq!(pools[1].quote_options().anchor, PriceAnchor::Ema);
+    assert_eq!(pools[1].price_decimals, 1000);
+    // pyth only, deepbook sources are not even fallbacks
+    assert_eq!((pools[0].pyth_weight, pools[0].mid_weight, pools[0].vwap_weight, pools[0].min_sources), (1.0, 0.0, 0.0, 1));
+    assert!(!pools[0].price_fallback);
+    assert_eq!(pools[0].onchain_objects().len(), 0);
+}
+
+#[test]
+fn decide_test() {
+    let pool: PoolConfig = serde_json::from_str(r#"{"pool": "0x4405b50d791fd3346754e8171aaab6bc2ed26c2c46efdd033c14b30ae507ac33"}"#).unwrap();
+    let config = OracleConfig::default();
+    let stats = MarketSnapshot::default();
+    let price = ValidatedPrice{ price: 1.0, conf: 0.001, ema_price: 1.0, ema_conf: 0.001, publish_time: 0 };
+    let balance = vec![100_000_000_000, 0, 100_000_000, 0];
+    let book = vec![990_000, 1_010_000];
+    let now = 1_000_000;
+    let order = Order{ order_id: 1, client_order_id: 0, price: 1_000_000, original_quantity: 1, quantity: 1,
+        is_bid: true, owner: SuiAddress::ZERO, expire_timestamp: now + 3_600_000, self_matching_prevention: 0 };
+
+    // no volatility yet
+    assert!(matches!(decide(&balance, &book, &vec![], Some(price), None, &stats, &pool, &config, now), QuoteAction::Wait));
+    // no oracle price, nothing to cancel
+    assert!(matches!(decide(&balance, &book, &vec![], None, Some(0.01), &stats, &pool, &config, now), QuoteAction::Wait));
+    // no oracle price, resting orders are pulled
+    let open = vec![order];
+    assert!(matches!(decide(&balance, &book, &open, None, Some(0.01), &stats, &pool, &config, now), QuoteAction::CancelAll));
+    // oracle price 5 percent away from mid
+    let far = ValidatedPrice{ price: 1.05, ..price };
+    assert!(matches!(decide(&balance, &book, &open, Some(far), Some(0.01), &stats, &pool, &config, now), QuoteAction::CancelAll));
+    // resting order at the oracle price is kept
+    assert!(matches!(decide(&balance, &book, &open, Some(price), Some(0.01), &stats, &pool, &config, now), QuoteAction::Wait));
+    // resting order about to expire
+    assert!(matches!(decide(&balance, &book, &open, Some(price), Some(0.01), &stats, &pool, &config, now + 3_590_000), QuoteAction::CancelAll));
+}
+
+#[test]
+fn apply_inventory_test() {
+    let pool: PoolConfig = serde_json::from_str(r#"{"pool": "0x4405b50d791fd3346754e8171aaab6bc2ed26c2c46efdd033c14b30ae507ac33",
+        "inventory": {"max": 50000000000, "hard_max": 80000000000, "flatten_slippage": 0.01}}"#).unwrap();
+    let config = OracleConfig::default();
+    let price = ValidatedPrice{ price: 1.0, conf: 0.001, ema_price: 1.0, ema_conf: 0.001, publish_time: 0 };
+    let balance = vec![100_000_000_000, 0, 100_000_000, 0];
+    let inventory = Inventory::new(&balance, 0, 0);
+    let book = vec![990_000, 1_010_000];
+
+    assert!(matches!(apply_inventory(QuoteAction::Wait, &inventory, &balance, &book, Some(price), &pool, &config, 1_000, 100_000_000), QuoteAction::Flatten(_)));
+    // oracle rejected by decide, cancel is kept
+    let far = ValidatedPrice{ price: 1.5, ..price };
+    assert!(matches!(apply_inventory(QuoteAction::CancelAll, &inventory, &balance, &book, Some(far), &pool, &config, 1_000, 100_000_000), QuoteAction::CancelAll));
+    assert!(matches!(apply_inventory(QuoteAction::CancelAll, &inventory, &balance, &book, None, &pool, &config, 1_000, 100_000_000), QuoteAction::CancelAll));
+}
+
+#[test]
+fn quote_volatility_test() {
+    let mut volatility = QuoteVolatility::new(1);
+    let price = |publish_time: i64, price: f64| ValidatedPrice{ price, conf: 0.0, ema_price: price, ema_conf: 0.0, publish_time };
+    for i in 0..300 {
+        volatility.update(&price(1_700_000_000 + i, if i % 2 == 0 { 1.0 } else { 1.01 }));
+        // a repeated publish time is not a new sample
+        volatility.update(&price(1_700_000_000 + i, 2.0));
+    }
+    assert!(volatility.volatility().is_none());
+    volatility.update(&price(1_700_000_300, 1.0));
+    let result = volatility.volatility().unwrap();
+    // every tick moves by about 1%, over 300 ticks that is about sqrt(300)%
+    assert!((result - 1.01f64.ln() * 300f64.sqrt()).abs() < 1e-3);
+}
+
+#[test]
+fn quote_volatility_parkinson_test() {
+    let mut volatility = QuoteVolatility::new(1);
+    let price = |publish_time: i64, price: f64| ValidatedPrice{ price, conf: 0.0, ema_price: price, ema_conf: 0.0, publish_time };
+    // a steady trend, every one minute candle ranges over 59 ticks
+    let r = 1.0001f64.ln();
+    for i in 0..961 {
+        volatility.update(&price(1_700_000_040 + i, (r * i as f64).exp()));
+    }
+    let expected = 59.0 * r * (300_000.0 / (4.0 * 2f64.ln() * 60_000.0)).sqrt();
+    assert!((volatility.volatility().unwrap() - expected).abs() < 1e-6);
+    assert!(expected > r * 300f64.sqrt());
+}
+
+#[test]
+fn transaction_batches_test() {
+    let (cap, other_cap) = (ObjectID::random(), ObjectID::random());
+    let caps = vec![cap, cap, cap, other_cap];
+    let flatten = OrderRequest{ price: 1_000_000, quantity: 100_000_000, is_bid: true };
+    let actions = vec![(0, QuoteAction::CancelAll), (1, QuoteAction::Flatten(flatten)), (2, QuoteAction::Wait)];
+    // two pools of one account cap go out in one transaction
+    let batches = transaction_batches(&caps, actions);
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].iter().map(|x| x.0).collect::<Vec<usize>>(), vec![0, 1]);
+    // another account cap needs its own transaction
+    let batches = transaction_batches(&caps, vec![(0, QuoteAction::CancelAll), (3, QuoteAction::CancelAll), (1, QuoteAction::CancelAll)]);
+    assert_eq!(batches.iter().map(|x| x.iter().map(|y| y.0).collect()).collect::<Vec<Vec<usize>>>(), vec![vec![0, 1], vec![3]]);
+}
@@ -0,0 +1,90 @@
+use fastcrypto::encoding::{Base64, Encoding};
+use sui_mm::signer::{parse_scheme, verify_signature, KeystoreSigner, RemoteSignRequest, RemoteSignResponse, RemoteSigner, Signer};
+use sui_types::base_types::{ObjectID, SequenceNumber, SuiAddress};
+use sui_types::crypto::SignatureScheme;
+use sui_types::digests::ObjectDigest;
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::transaction::TransactionData;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixListener;
+
+const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+fn tx_data(sender: SuiAddress) -> TransactionData {
+    let gas = (ObjectID::random(), SequenceNumber::from_u64(1), ObjectDigest::random());
+    return TransactionData::new_programmable(sender, vec![gas], ProgrammableTransactionBuilder::new().finish(), 1_000_000, 1_000);
+}
+
+// answers one request on the socket with a signature of signer
+async fn serve_once(listener: UnixListener, signer: KeystoreSigner) {
+    let (stream, _) = listener.accept().await.unwrap();
+    let (reader, mut writer) = stream.into_split();
+    let mut line = String::new();
+    BufReader::new(reader).read_line(&mut line).await.unwrap();
+    let request: RemoteSignRequest = serde_json::from_str(&line).unwrap();
+    let tx_data: TransactionData = bcs::from_bytes(&Base64::decode(&request.tx_bytes).unwrap()).unwrap();
+    let signature = signer.sign(&tx_data).await.unwrap();
+    let response = RemoteSignResponse{ signature: Base64::encode(signature.as_ref()) };
+    writer.write_all((serde_json::to_string(&response).unwrap() + "\n").as_bytes()).await.unwrap();
+}
+
+#[test]
+fn mnemonic_signer_test() {
+    assert_eq!(parse_scheme("Secp256k1"), Some(SignatureScheme::Secp256k1));
+    assert_eq!(parse_scheme("rsa"), None);
+
+    let default_path = KeystoreSigner::from_mnemonic(MNEMONIC, SignatureScheme::ED25519, None);
+    let first = KeystoreSigner::from_mnemonic(MNEMONIC, SignatureScheme::ED25519, Some("m/44'/784'/0'/0'/0'".to_string()));
+    let second = KeystoreSigner::from_mnemonic(MNEMONIC, SignatureScheme::ED25519, Some("m/44'/784'/1'/0'/0'".to_string()));
+    assert_eq!(default_path.address(), first.address());
+    assert_ne!(first.address(), second.address());
+}
+
+#[tokio::test]
+async fn verify_signature_test() {
+    let signer = KeystoreSigner::from_mnemonic(MNEMONIC, SignatureScheme::ED25519, None);
+    let other = KeystoreSigner::from_mnemonic(MNEMONIC, SignatureScheme::ED25519, Some("m/44'/784'/1'/0'/0'".to_string()));
+    let tx = tx_data(signer.address());
+    let signature = signer.sign(&tx).await.unwrap();
+    assert!(verify_signature(&signature, &tx, signer.address()));
+    assert!(!verify_signature(&signature, &tx, other.address()));
+    // signature of different transaction data
+    assert!(!verify_signature(&signature, &tx_data(signer.address()), signer.address()));
+}
+
+#[tokio::test]
+async fn unix_remote_signer_test() {
+    let path = std::env::temp_dir().join(format!("signer_test_{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let address = KeystoreSigner::from_mnemonic(MNEMONIC, SignatureScheme::ED25519, None).address();
+    let remote = RemoteSigner::new(format!("unix://{}", path.display()), address, 2_000);
+    let tx = tx_data(address);
+
+    let listener = UnixListener::bind(&path).unwrap();
+    let server = tokio::spawn(serve_once(listener, KeystoreSigner::from_mnemonic(MNEMONIC, SignatureScheme::ED25519, None)));
+    assert!(remote.sign(&tx).await.is_some());
+    server.await.unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // signer answering with a different key is rejected
+    let listener = UnixListener::bind(&path).unwrap();
+    let wrong_key = KeystoreSigner::from_mnemonic(MNEMONIC, SignatureScheme::ED25519, Some("m/44'/784'/1'/0'/0'".to_string()));
+    let server = tokio::spawn(serve_once(listener, wrong_key));
+    assert!(remote.sign(&tx).await.is_none());
+    server.await.unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // signer accepting the connection but never answering times out
+    let listener = UnixListener::bind(&path).unwrap();
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+        drop(stream);
+    });
+    let remote = RemoteSigner::new(format!("unix://{}", path.display()), address, 100);
+    let started = std::time::Instant::now();
+    assert!(remote.sign(&tx).await.is_none());
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    server.abort();
+    std::fs::remove_file(&path).unwrap();
+}
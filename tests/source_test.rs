@@ -0,0 +1,73 @@
+use async_trait::async_trait;
+use sui_mm::oracle::{check_mid_deviation, OracleConfig, OracleError, ValidatedPrice};
+use sui_mm::source::{weighted_median, PriceAggregator, PriceSource};
+
+fn price(value: f64) -> ValidatedPrice {
+    return ValidatedPrice{ price: value, conf: 0.0, ema_price: value, ema_conf: 0.0, publish_time: 0 }
+}
+
+struct FixedSource {
+    price: Result<ValidatedPrice, OracleError>,
+    book_mid: bool,
+}
+
+#[async_trait]
+impl PriceSource for FixedSource {
+    fn name(&self) -> String {
+        return "fixed".to_string();
+    }
+
+    fn is_book_mid(&self) -> bool {
+        return self.book_mid;
+    }
+
+    async fn get_price(&self) -> Result<ValidatedPrice, OracleError> {
+        return self.price.clone();
+    }
+}
+
+#[test]
+fn weighted_median_test() {
+    let prices = vec![(price(1.2), 1.0), (price(1.0), 1.0), (price(5.0), 1.0)];
+    assert_eq!(weighted_median(&prices).unwrap().price, 1.2);
+    // heavier weight pulls the median
+    let prices = vec![(price(1.2), 1.0), (price(1.0), 3.0), (price(5.0), 1.0)];
+    assert_eq!(weighted_median(&prices).unwrap().price, 1.0);
+    assert!(weighted_median(&vec![]).is_none());
+}
+
+#[tokio::test]
+async fn aggregator_fallback_test() {
+    // pyth is down and fallback is off, no price so that quoting stops
+    let aggregator = PriceAggregator::new(1)
+        .add(Box::new(FixedSource{ price: Err(OracleError::Unavailable), book_mid: false }), 1.0)
+        .add(Box::new(FixedSource{ price: Ok(price(1.5)), book_mid: true }), 0.0);
+    assert_eq!(aggregator.get_price_with_source().await, Err(OracleError::Unavailable));
+    // with fallback the book mid is used and reported as such
+    let aggregator = aggregator.with_fallback(true);
+    assert_eq!(aggregator.get_price_with_source().await, Ok((price(1.5), true)));
+    // a weighted source with the same price as the mid is not the mid
+    let aggregator = PriceAggregator::new(1)
+        .add(Box::new(FixedSource{ price: Ok(price(1.5)), book_mid: false }), 1.0)
+        .add(Box::new(FixedSource{ price: Ok(price(1.5)), book_mid: true }), 0.0)
+        .with_fallback(true);
+    assert_eq!(aggregator.get_price_with_source().await, Ok((price(1.5), false)));
+    let aggregator = PriceAggregator::new(1)
+        .add(Box::new(FixedSource{ price: Ok(price(1.5)), book_mid: true }), 1.0)
+        .add(Box::new(FixedSource{ price: Ok(price(1.5)), book_mid: false }), 1.0);
+    assert_eq!(aggregator.get_price_with_source().await, Ok((price(1.5), true)));
+    let aggregator = PriceAggregator::new(1)
+        .add(Box::new(FixedSource{ price: Ok(price(1.4)), book_mid: false }), 1.0)
+        .add(Box::new(FixedSource{ price: Ok(price(1.5)), book_mid: true }), 0.0);
+    assert_eq!(aggregator.get_price_with_source().await, Ok((price(1.4), false)));
+    let aggregator = PriceAggregator::new(1)
+        .add(Box::new(FixedSource{ price: Err(OracleError::Unavailable), book_mid: true }), 1.0);
+    assert_eq!(aggregator.get_price().await, Err(OracleError::Unavailable));
+}
+
+#[test]
+fn without_mid_check_test() {
+    let config = OracleConfig::default();
+    assert!(check_mid_deviation(1.5, 1_790_000, 1_810_000, 1_000_000, &config).is_err());
+    assert!(check_mid_deviation(1.5, 1_790_000, 1_810_000, 1_000_000, &config.without_mid_check()).is_ok());
+}
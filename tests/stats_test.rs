@@ -0,0 +1,110 @@
+use serde_json::Value;
+use sui_mm::events::{OrderFilledEvent, PoolCreatedEvent, TypeName};
+use sui_mm::stats::{MarketStats, Trade, HOUR_MS, MAX_MID_AGE_MS, MINUTE_MS};
+use sui_sdk::rpc_types::SuiEvent;
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::digests::TransactionDigest;
+use sui_types::event::EventID;
+use sui_types::parse_sui_struct_tag;
+
+const POOL: &str = "0x4405b50d791fd3346754e8171aaab6bc2ed26c2c46efdd033c14b30ae507ac33";
+const OTHER_POOL: &str = "0x7f526b1263c4b91b43c9e646419b5696f424de28dda3c1e6658cc0a54558baa7";
+const NOW: u64 = 1_709_251_200_000;
+
+fn trade(age_ms: u64, price: u64, quantity: u64, taker_buy: bool, mid: Option<f64>) -> Trade {
+    return Trade{ timestamp_ms: NOW - age_ms, price, quantity, taker_buy, mid };
+}
+
+#[test]
+fn window_stats_test() {
+    let mut stats = MarketStats::with_defaults(POOL.parse().unwrap());
+    stats.push(trade(30_000, 1_001_000, 10, true, Some(1_000_000.0)));
+    stats.push(trade(7_200_000, 1_000_000, 5, true, None));
+    stats.push(trade(1_800_000, 1_000_000, 60, true, None));
+    stats.push(trade(120_000, 998_000, 30, false, Some(1_000_000.0)));
+    // trades older than the longest window are dropped
+    let snapshot = stats.snapshot(NOW);
+    assert_eq!(stats.len(), 3);
+
+    let minute = snapshot.window(MINUTE_MS).unwrap();
+    assert_eq!((minute.count, minute.volume, minute.imbalance, minute.average_size), (1, 10.0, 1.0, 10.0));
+    assert!((minute.vwap.unwrap() - 1_001_000.0).abs() < 1e-6);
+    let rates: Vec<(f64, f64, f64)> = minute.arrival.iter().map(|x| (x.distance_bps, x.bid_per_s, x.ask_per_s)).collect();
+    assert_eq!(rates[..4], [(0.0, 0.0, 1.0 / 60.0), (5.0, 0.0, 1.0 / 60.0), (10.0, 0.0, 1.0 / 60.0), (20.0, 0.0, 0.0)]);
+
+    let five = snapshot.window(5 * MINUTE_MS).unwrap();
+    assert_eq!((five.count, five.buy_volume, five.sell_volume, five.imbalance), (2, 10.0, 30.0, -0.5));
+    assert!((five.vwap.unwrap() - 998_750.0).abs() < 1e-6);
+    assert_eq!((five.arrival[3].bid_per_s, five.arrival[4].bid_per_s), (1.0 / 300.0, 0.0));
+
+    let hour = snapshot.window(HOUR_MS).unwrap();
+    assert_eq!((hour.count, hour.volume, hour.imbalance), (3, 100.0, 0.4));
+    assert!((hour.notional - (10.0 * 1_001_000.0 + 30.0 * 998_000.0 + 60.0 * 1_000_000.0) / 1e9).abs() < 1e-12);
+    assert!((hour.notional_per_s() - hour.notional / 3600.0).abs() < 1e-15);
+
+    // no trades, no division by zero
+    let empty = MarketStats::with_defaults(POOL.parse().unwrap()).snapshot(NOW);
+    let minute = empty.window(MINUTE_MS).unwrap();
+    assert_eq!((minute.count, minute.vwap, minute.imbalance, minute.average_size), (0, None, 0.0, 0.0));
+    assert!(minute.arrival.iter().all(|x| x.bid_per_s == 0.0 && x.ask_per_s == 0.0));
+}
+
+fn sui_event(type_: &str, parsed_json: Value) -> SuiEvent {
+    return SuiEvent{
+        id: EventID{ tx_digest: TransactionDigest::random(), event_seq: 0 },
+        package_id: ObjectID::from_hex_literal("0xdee9").unwrap(),
+        transaction_module: "clob_v2".parse().unwrap(),
+        sender: SuiAddress::ZERO,
+        type_: parse_sui_struct_tag(type_).unwrap(),
+        parsed_json,
+        bcs: vec![],
+        timestamp_ms: Some(NOW),
+    }
+}
+
+fn filled(pool: &str) -> SuiEvent {
+    // maker is bid, taker sold
+    let event = OrderFilledEvent{ pool_id: pool.parse().unwrap(), order_id: 1, taker_client_order_id: 1, maker_client_order_id: 2,
+        original_quantity: 100, base_asset_quantity_filled: 50, base_asset_quantity_remaining: 50, is_bid: true,
+        maker_address: SuiAddress::ZERO, taker_address: SuiAddress::ZERO, price: 1_783_700, taker_commission: 10, maker_rebates: 5 };
+    return sui_event("0xdee9::clob_v2::OrderFilled<0x2::sui::SUI, 0x2::sui::SUI>", serde_json::to_value(&event).unwrap());
+}
+
+#[test]
+fn push_event_test() {
+    let mut stats = MarketStats::with_defaults(POOL.parse().unwrap());
+    stats.set_mid(NOW - 1_000, 1_784_000.0);
+    // sampled after the fill, not used for it
+    stats.set_mid(NOW + 1_000, 1_700_000.0);
+    assert!(stats.push_event(&filled(POOL)));
+    assert!(!stats.push_event(&filled(OTHER_POOL)));
+    let created = PoolCreatedEvent{ pool_id: POOL.parse().unwrap(), base_asset: TypeName{ name: "a".to_string() }, quote_asset: TypeName{ name: "b".to_string() },
+        taker_fee_rate: 0, maker_rebate_rate: 0, tick_size: 1, lot_size: 1 };
+    assert!(!stats.push_event(&sui_event("0xdee9::clob_v2::PoolCreated", serde_json::to_value(&created).unwrap())));
+    assert_eq!(stats.len(), 1);
+
+    let minute = stats.snapshot(NOW).windows[0].clone();
+    assert_eq!((minute.count, minute.sell_volume), (1, 50.0));
+    assert!((minute.vwap.unwrap() - 1_783_700.0).abs() < 1e-6);
+    // 1.7 bps below mid
+    assert_eq!((minute.arrival[0].bid_per_s, minute.arrival[1].bid_per_s), (1.0 / 60.0, 0.0));
+}
+
+#[test]
+fn mid_at_test() {
+    let mut stats = MarketStats::with_defaults(POOL.parse().unwrap());
+    assert_eq!(stats.mid_at(NOW), None);
+    stats.set_mid(NOW - 10_000, 1.0);
+    stats.set_mid(NOW, 3.0);
+    // out of order sample
+    stats.set_mid(NOW - 5_000, 2.0);
+    assert_eq!(stats.mid_at(NOW - 20_000), None);
+    assert_eq!(stats.mid_at(NOW - 7_000), Some(1.0));
+    assert_eq!(stats.mid_at(NOW - 1), Some(2.0));
+    assert_eq!(stats.mid_at(NOW), Some(3.0));
+    assert_eq!(stats.mid_at(NOW + MAX_MID_AGE_MS + 1), None);
+    // old samples are dropped, the last one is kept
+    stats.snapshot(NOW + 2 * HOUR_MS);
+    assert_eq!(stats.mid_at(NOW + 2 * HOUR_MS), None);
+    assert_eq!(stats.mid_at(NOW + 1_000), Some(3.0));
+}
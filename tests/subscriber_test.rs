@@ -0,0 +1,128 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use sui_mm::events::OrderFilledEvent;
+use sui_mm::subscriber::{parse_message, EventBackfill, EventSubscriber, RpcMessage, SUBSCRIBE_METHOD};
+use sui_sdk::rpc_types::{EventFilter, SuiEvent};
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::digests::TransactionDigest;
+use sui_types::event::EventID;
+use sui_types::parse_sui_struct_tag;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+const POOL: &str = "0x4405b50d791fd3346754e8171aaab6bc2ed26c2c46efdd033c14b30ae507ac33";
+const OTHER_POOL: &str = "0x7f526b1263c4b91b43c9e646419b5696f424de28dda3c1e6658cc0a54558baa7";
+const USDC: &str = "0x5d4b302506645c37ff133b98c4b50a5ae14841659738d6d733d59d0d217a93bf::coin::COIN";
+
+fn sui_event(name: &str, parsed_json: Value, seq: u64) -> SuiEvent {
+    return SuiEvent{
+        id: EventID{ tx_digest: TransactionDigest::random(), event_seq: seq },
+        package_id: ObjectID::from_hex_literal("0xdee9").unwrap(),
+        transaction_module: "clob_v2".parse().unwrap(),
+        sender: SuiAddress::ZERO,
+        type_: parse_sui_struct_tag(&format!("0xdee9::clob_v2::{name}<0x2::sui::SUI, {USDC}>")).unwrap(),
+        parsed_json,
+        bcs: vec![],
+        timestamp_ms: Some(1_708_380_292_894 + seq),
+    }
+}
+
+fn filled(pool: &str, seq: u64) -> SuiEvent {
+    let event = OrderFilledEvent{ pool_id: pool.parse().unwrap(), order_id: seq, taker_client_order_id: 1, maker_client_order_id: 2,
+        original_quantity: 100, base_asset_quantity_filled: 50, base_asset_quantity_remaining: 50, is_bid: true,
+        maker_address: SuiAddress::ZERO, taker_address: SuiAddress::ZERO, price: 1_783_700, taker_commission: 10, maker_rebates: 5 };
+    return sui_event("OrderFilled", serde_json::to_value(&event).unwrap(), seq);
+}
+
+fn notification(event: &SuiEvent) -> String {
+    return json!({"jsonrpc": "2.0", "method": SUBSCRIBE_METHOD, "params": {"subscription": 7, "result": event}}).to_string();
+}
+
+struct MockBackfill {
+    cursors: Arc<Mutex<Vec<EventID>>>,
+    events: Vec<SuiEvent>,
+}
+
+#[async_trait]
+impl EventBackfill for MockBackfill {
+    async fn events_after(&self, _filter: &EventFilter, cursor: EventID) -> Result<Vec<SuiEvent>, String> {
+        self.cursors.lock().unwrap().push(cursor);
+        return Ok(self.events.clone());
+    }
+}
+
+// one connection per list of events, acknowledges the subscription, sends the events and closes
+async fn mock_server(sessions: Vec<Vec<SuiEvent>>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    tokio::spawn(
+        async move {
+            for events in sessions {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+                let request = socket.next().await.unwrap().unwrap().into_text().unwrap();
+                let request: Value = serde_json::from_str(&request).unwrap();
+                assert_eq!(request["method"], SUBSCRIBE_METHOD);
+                socket.send(Message::Text(json!({"jsonrpc": "2.0", "result": 7, "id": request["id"]}).to_string())).await.unwrap();
+                for event in events.iter() {
+                    socket.send(Message::Text(notification(event))).await.unwrap();
+                }
+                socket.close(None).await.unwrap();
+            }
+        }
+    );
+    return url;
+}
+
+#[tokio::test]
+async fn reconnect_backfill_test() {
+    let (a, b, c, x, d) = (filled(POOL, 0), filled(OTHER_POOL, 1), filled(POOL, 2), filled(POOL, 3), filled(POOL, 4));
+    // c comes again from backfill and from the second connection
+    let url = mock_server(vec![vec![a.clone(), b.clone(), c.clone()], vec![d.clone(), c.clone()]]).await;
+    let cursors = Arc::new(Mutex::new(vec![]));
+    let backfill = MockBackfill{ cursors: Arc::clone(&cursors), events: vec![c.clone(), x.clone()] };
+    let (sender, mut receiver) = mpsc::channel(16);
+    let handle = EventSubscriber::new(url, vec![POOL.parse().unwrap()], backfill, None, 10).spawn(sender);
+    let mut received = vec![];
+    while received.len() < 4 {
+        let event = tokio::time::timeout(Duration::from_secs(5), receiver.recv()).await.unwrap().unwrap();
+        received.push(event.id);
+    }
+    assert_eq!(received, vec![a.id, c.id, x.id, d.id]);
+    // first connection has no cursor to backfill from
+    assert_eq!(*cursors.lock().unwrap(), vec![c.id]);
+    handle.abort();
+}
+
+#[tokio::test]
+async fn shared_subscription_test() {
+    let third_pool = "0xe405bba35e24a8c9fb45a7bbfbdcf46ce5138ef4f1d5b2c9a4d3e6a8b7c1f2d3";
+    let (a, b, c, d) = (filled(POOL, 0), filled(OTHER_POOL, 1), filled(third_pool, 2), filled(OTHER_POOL, 3));
+    let url = mock_server(vec![vec![a.clone(), b.clone(), c.clone(), d.clone()]]).await;
+    let backfill = MockBackfill{ cursors: Arc::new(Mutex::new(vec![])), events: vec![] };
+    let (sender, mut receiver) = mpsc::channel(16);
+    let pools = vec![POOL.parse().unwrap(), OTHER_POOL.parse().unwrap()];
+    let handle = EventSubscriber::new(url, pools, backfill, None, 10).spawn(sender);
+    let mut received = vec![];
+    while received.len() < 3 {
+        let event = tokio::time::timeout(Duration::from_secs(5), receiver.recv()).await.unwrap().unwrap();
+        received.push(event.id);
+    }
+    // events of both pools on one connection, third pool is dropped
+    assert_eq!(received, vec![a.id, b.id, d.id]);
+    handle.abort();
+}
+
+#[test]
+fn parse_message_test() {
+    assert!(matches!(parse_message(r#"{"jsonrpc":"2.0","result":3,"id":1}"#), RpcMessage::Subscribed(x) if x == 3));
+    let event = filled(POOL, 5);
+    assert!(matches!(parse_message(&notification(&event)), RpcMessage::Event(x) if x.id == event.id && x.parsed_json == event.parsed_json));
+    assert!(matches!(parse_message(r#"{"jsonrpc":"2.0","error":{"code":-32601,"message":"Method not found"},"id":1}"#), RpcMessage::Error(_)));
+    assert!(matches!(parse_message("not json"), RpcMessage::Error(_)));
+    assert!(matches!(parse_message(r#"{"jsonrpc":"2.0","method":"other","params":{}}"#), RpcMessage::Other));
+}
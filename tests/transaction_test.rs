@@ -0,0 +1,34 @@
+use sui_mm::transaction::{clob_v2_error_name, estimate_budget, is_deterministic_error, parse_move_abort, GasConfig, MoveAbort};
+use sui_types::gas::GasCostSummary;
+
+#[test]
+fn parse_move_abort_test() {
+    let error = "MoveAbort(MoveLocation { module: ModuleId { address: dee9, name: Identifier(\"clob_v2\") }, function: 33, instruction: 39, function_name: Some(\"place_limit_order_int\") }, 10) in command 0";
+    assert_eq!(parse_move_abort(error), Some(MoveAbort{ module: "clob_v2".to_string(), code: 10, name: Some("EOrderCannotBeFullyPassive".to_string()) }));
+
+    let error = "MoveAbort(MoveLocation { module: ModuleId { address: 2, name: Identifier(\"balance\") }, function: 12, instruction: 21, function_name: Some(\"split\") }, 2) in command 1";
+    assert_eq!(parse_move_abort(error), Some(MoveAbort{ module: "balance".to_string(), code: 2, name: None }));
+
+    assert_eq!(parse_move_abort("InsufficientGas"), None);
+    assert_eq!(clob_v2_error_name(8), Some("EInsufficientQuoteCoin"));
+    assert_eq!(clob_v2_error_name(1), Some("EIncorrectPoolOwner"));
+    assert_eq!(clob_v2_error_name(15), None);
+}
+
+#[test]
+fn estimate_budget_test() {
+    let config = GasConfig::default();
+    let summary = GasCostSummary::new(1_000_000, 4_000_000, 3_000_000, 0);
+    assert_eq!(estimate_budget(&summary, &config), 6_000_000);
+    assert_eq!(estimate_budget(&GasCostSummary::new(1_000, 0, 0, 0), &config), config.min_budget);
+    assert_eq!(estimate_budget(&GasCostSummary::new(100_000_000, 0, 0, 0), &config), config.max_budget);
+}
+
+#[test]
+fn deterministic_error_test() {
+    assert!(is_deterministic_error("Transaction validator signing failed due to issues with transaction inputs, please review the errors and try again: [ObjectVersionUnavailableForConsumption { provided_obj_ref: (0x5, SequenceNumber(7)), current_version: SequenceNumber(8) }]"));
+    assert!(is_deterministic_error("Error checking transaction input objects: GasBalanceTooLow { gas_balance: 10, needed_gas_amount: 2000000 }"));
+    assert!(is_deterministic_error("Invalid user signature: Required Signature from 0x1 is absent"));
+    assert!(!is_deterministic_error("error trying to connect: tcp connect error: Connection refused (os error 111)"));
+    assert!(!is_deterministic_error("Networking or low-level protocol error: HTTP error: 503"));
+}
@@ -0,0 +1,107 @@
+use sui_mm::volatility::{EwmaVolatility, GarchVolatility, OhlcBar, ParkinsonVolatility, PriceSample, RealizedVolatility, Volatility, VolatilityEstimator, YEAR_MS};
+
+// price moving up and down by 1 percent every interval_ms
+fn zigzag(count: usize, interval_ms: u64) -> Vec<PriceSample> {
+    return (0..count).map(|i| PriceSample{ timestamp_ms: 1_000_000 + i as u64 * interval_ms, price: if i % 2 == 0 {100.0} else {101.0} }).collect();
+}
+
+fn close(a: f64, b: f64) -> bool {
+    return (a - b).abs() <= 1e-9 * b.abs().max(1e-300);
+}
+
+#[test]
+fn legacy_volatility_test() {
+    let mut volatility = Volatility::new(3);
+    volatility.insert(1.0);
+    volatility.insert(2.0);
+    assert_eq!(volatility.volatility(), None);
+    volatility.insert(3.0);
+    volatility.insert(4.0);
+    assert!(volatility.is_ok());
+    assert_eq!(volatility.prices, vec![4.0, 3.0, 2.0]);
+    // sample standard deviation 1 over mean 3
+    assert!(close(volatility.volatility().unwrap(), 3f64.sqrt() / 3.0));
+}
+
+#[test]
+fn realized_volatility_test() {
+    let r = (101.0f64 / 100.0).ln();
+    let mut realized = RealizedVolatility::new(10);
+    for sample in zigzag(10, 1_000) {
+        realized.update(sample);
+    }
+    // 9 returns of 10
+    assert_eq!(realized.variance_per_ms(), None);
+    realized.update(PriceSample{ timestamp_ms: 1_010_000, price: 100.0 });
+    assert!(close(realized.variance_per_ms().unwrap(), r * r / 1_000.0));
+    assert!(close(realized.volatility_over(1_000.0).unwrap(), r));
+    assert!(close(realized.annualized().unwrap(), r * (YEAR_MS / 1_000.0).sqrt()));
+    // out of order and non positive samples are skipped
+    realized.update(PriceSample{ timestamp_ms: 1_005_000, price: 50.0 });
+    realized.update(PriceSample{ timestamp_ms: 1_011_000, price: 0.0 });
+    assert!(close(realized.variance_per_ms().unwrap(), r * r / 1_000.0));
+
+    // same moves twice as far apart are half the variance rate
+    let mut slow = RealizedVolatility::new(10);
+    for sample in zigzag(11, 2_000) {
+        slow.update(sample);
+    }
+    assert!(close(slow.variance_per_ms().unwrap(), r * r / 2_000.0));
+}
+
+#[test]
+fn ewma_volatility_test() {
+    let r = (101.0f64 / 100.0).ln();
+    let mut ewma = EwmaVolatility::riskmetrics(1_000.0, 5);
+    let samples = zigzag(12, 1_000);
+    for sample in samples[..5].iter() {
+        ewma.update(*sample);
+    }
+    assert_eq!(ewma.variance_per_ms(), None);
+    for sample in samples[5..].iter() {
+        ewma.update(*sample);
+    }
+    assert!(close(ewma.variance_per_ms().unwrap(), r * r / 1_000.0));
+    // a quiet sample decays the variance by lambda
+    ewma.update(PriceSample{ timestamp_ms: 1_012_000, price: 101.0 });
+    assert!(close(ewma.variance_per_ms().unwrap(), 0.94 * r * r / 1_000.0));
+    // after two units the previous estimate weighs lambda squared
+    ewma.update(PriceSample{ timestamp_ms: 1_014_000, price: 100.0 });
+    let decay = 0.94f64 * 0.94;
+    assert!(close(ewma.variance_per_ms().unwrap(), decay * 0.94 * r * r / 1_000.0 + (1.0 - decay) * r * r / 2_000.0));
+}
+
+#[test]
+fn parkinson_volatility_test() {
+    let mut parkinson = ParkinsonVolatility::new(2);
+    let bar = OhlcBar{ start_ms: 0, duration_ms: 60_000, open: 100.0, high: 100.0 * 0.01f64.exp(), low: 100.0, close: 100.0 };
+    parkinson.update(bar);
+    assert_eq!(parkinson.variance_per_ms(), None);
+    parkinson.update(OhlcBar{ start_ms: 60_000, ..bar });
+    // invalid bar is skipped
+    parkinson.update(OhlcBar{ low: 0.0, ..bar });
+    assert!(close(parkinson.variance_per_ms().unwrap(), 0.0001 / (4.0 * 2f64.ln() * 60_000.0)));
+}
+
+#[test]
+fn garch_volatility_test() {
+    let r = (101.0f64 / 100.0).ln();
+    let rate = r * r / 1_000.0;
+    let mut garch = GarchVolatility::with_long_run(rate, 0.1, 0.85, 3);
+    assert!(close(garch.long_run(), rate));
+    for sample in zigzag(3, 1_000) {
+        garch.update(sample);
+    }
+    assert_eq!(garch.variance_per_ms(), None);
+    // returns at the long run rate keep the variance there
+    garch.update(zigzag(4, 1_000)[3]);
+    assert!(close(garch.variance_per_ms().unwrap(), rate));
+
+    // shock decays back to the long run
+    garch.update(PriceSample{ timestamp_ms: 1_004_000, price: 110.0 });
+    let shocked = garch.variance_per_ms().unwrap();
+    assert!(shocked > rate);
+    assert!(close(garch.forecast(0), shocked));
+    assert!(close(garch.forecast(1), rate + 0.95 * (shocked - rate)));
+    assert!(garch.forecast(1_000) - rate < 1e-12 * rate);
+}